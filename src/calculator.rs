@@ -0,0 +1,280 @@
+use anyhow::{anyhow, Error};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::calendar::Calendars;
+use crate::fixings::RateFixings;
+use crate::fx::FxRates;
+use crate::history::{self, Amendment, LoanVersion};
+use crate::storage::JsonStorage;
+use crate::validation::ValidationRules;
+use crate::Loan;
+
+/// Returned when a loan ID does not exist in the LoanCalculator.
+#[derive(Debug)]
+pub struct LoanNotFound(pub u32);
+
+impl fmt::Display for LoanNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Loan with ID {} not found.", self.0)
+    }
+}
+
+impl std::error::Error for LoanNotFound {}
+
+/// Data shared by every loan's calculation, loaded from files rather than entered per loan.
+#[derive(Debug, Default)]
+pub struct MarketData {
+    pub fixings: RateFixings,
+    pub calendars: Calendars,
+    pub fx_rates: FxRates,
+}
+
+/// Outcome of loading market data into a calculator. Loans that fail to recalculate keep their previous figures.
+#[derive(Debug)]
+pub struct MarketDataLoad {
+    /// Number of fixings, rates or holidays loaded.
+    pub loaded: usize,
+    pub failed_loans: Vec<(u32, Error)>,
+}
+
+/// A book of loans with their amendment history, optionally saved to a JSON file after every change.
+#[derive(Debug)]
+pub struct LoanCalculator {
+    // BTreeMap is used as it is ordered by key and efficient for lookups.
+    // HashMap could be used for faster lookups but it is unordered so we do not use it here.
+    loans: BTreeMap<u32, Loan>,
+    // archived loans are hidden from listings and recalculation but kept for audit until restored or deleted
+    archived_loans: BTreeMap<u32, Loan>,
+    // only ever increases so the ID of a deleted loan is never given to another loan
+    next_loan_id: u32,
+    // append-only versions of each loan, kept after the loan is deleted
+    history: BTreeMap<u32, Vec<LoanVersion>>,
+    // recorded against every amendment made by this calculator
+    user: String,
+    validation_rules: ValidationRules,
+    market_data: MarketData,
+    // None keeps the loans in memory only
+    storage: Option<JsonStorage>,
+}
+
+impl Default for LoanCalculator {
+    fn default() -> Self {
+        LoanCalculator::new()
+    }
+}
+
+impl LoanCalculator {
+    /// Creates an empty calculator that keeps its loans in memory only.
+    pub fn new() -> Self {
+        LoanCalculator {
+            loans: BTreeMap::new(),
+            archived_loans: BTreeMap::new(),
+            next_loan_id: 1,
+            history: BTreeMap::new(),
+            user: history::login_name(),
+            validation_rules: ValidationRules::default(),
+            market_data: MarketData::default(),
+            storage: None,
+        }
+    }
+
+    /// Creates a calculator backed by `storage`, loading any loans saved in a previous session.
+    pub fn open(storage: JsonStorage) -> Result<Self, Error> {
+        let mut calculator = LoanCalculator::new();
+        if let Some(book) = storage.load()? {
+            // a book edited by hand could have a next ID that is already taken
+            let highest_loan_id = book.loans.keys().chain(book.archived_loans.keys()).max().copied().unwrap_or(0);
+            calculator.loans = book.loans;
+            calculator.archived_loans = book.archived_loans;
+            calculator.history = book.history;
+            calculator.next_loan_id = book.next_loan_id.max(highest_loan_id + 1);
+        }
+        calculator.storage = Some(storage);
+        Ok(calculator)
+    }
+
+    /// Active loans keyed by ID.
+    pub fn loans(&self) -> &BTreeMap<u32, Loan> {
+        &self.loans
+    }
+
+    pub fn archived_loans(&self) -> &BTreeMap<u32, Loan> {
+        &self.archived_loans
+    }
+
+    pub fn market_data(&self) -> &MarketData {
+        &self.market_data
+    }
+
+    pub fn storage(&self) -> Option<&JsonStorage> {
+        self.storage.as_ref()
+    }
+
+    /// Sets the user recorded against subsequent amendments.
+    pub fn set_user(&mut self, user: String) {
+        self.user = user;
+    }
+
+    pub fn set_validation_rules(&mut self, validation_rules: ValidationRules) {
+        self.validation_rules = validation_rules;
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        match &self.storage {
+            Some(storage) => storage.save(self.next_loan_id, &self.loans, &self.archived_loans, &self.history),
+            None => Ok(()),
+        }
+    }
+
+    /// Appends a version of the loan to its history, `previous` being the terms before the amendment.
+    fn record_version(&mut self, loan_id: u32, amendment: Amendment, previous: Option<&Loan>, loan: &Loan) -> Result<(), Error> {
+        let history = self.history.entry(loan_id).or_default();
+        history::record(history, amendment, &self.user, previous, loan)
+    }
+
+    /// Checks a loan's parameters, returning a `ValidationErrors` listing every invalid field.
+    pub fn validate(&self, loan: &Loan) -> Result<(), Error> {
+        Ok(self.validation_rules.validate(loan)?)
+    }
+
+    /// Validates a loan and calculates its interest with the calculator's market data, without adding it to the book.
+    pub fn calculate(&self, mut loan: Loan) -> Result<Loan, Error> {
+        self.validate(&loan)?;
+        loan.calculate_interest(&self.market_data)?;
+        Ok(loan)
+    }
+
+    /// Validates, calculates and adds a loan, returning its ID. Nothing is added if any step fails.
+    pub fn add_loan(&mut self, loan: Loan) -> Result<u32, Error> {
+        let loan = self.calculate(loan)?;
+        let loan_id = self.next_loan_id;
+        self.record_version(loan_id, Amendment::Created, None, &loan)?;
+        self.loans.insert(loan_id, loan);
+        self.next_loan_id += 1;
+        self.save()?;
+        Ok(loan_id)
+    }
+
+    /// Validates, calculates and adds several loans with a single save. Returns each loan's ID, or why it was
+    /// rejected, in the same order; rejected loans do not stop the others being added.
+    pub fn add_loans(&mut self, loans: Vec<Loan>) -> Result<Vec<Result<u32, Error>>, Error> {
+        let mut results = Vec::new();
+        for loan in loans {
+            let result = self.calculate(loan).and_then(|loan| {
+                let loan_id = self.next_loan_id;
+                self.record_version(loan_id, Amendment::Created, None, &loan)?;
+                self.loans.insert(loan_id, loan);
+                self.next_loan_id += 1;
+                Ok(loan_id)
+            });
+            results.push(result);
+        }
+        self.save()?;
+        Ok(results)
+    }
+
+    /// Validates and calculates the new terms of a loan, then replaces it. The loan is unchanged if any step fails.
+    pub fn update_loan(&mut self, loan_id: u32, updated_loan: Loan) -> Result<(), Error> {
+        if !self.loans.contains_key(&loan_id) {
+            return Err(LoanNotFound(loan_id).into());
+        }
+        let updated_loan = self.calculate(updated_loan)?;
+        let previous = self.loans.remove(&loan_id).ok_or(LoanNotFound(loan_id))?;
+        let recorded = self.record_version(loan_id, Amendment::Updated, Some(&previous), &updated_loan);
+        // put the old loan back if the amendment could not be recorded
+        self.loans.insert(loan_id, if recorded.is_ok() { updated_loan } else { previous });
+        recorded?;
+        self.save()
+    }
+
+    pub fn get_loan(&self, loan_id: u32) -> Result<&Loan, Error> {
+        self.loans.get(&loan_id).ok_or(LoanNotFound(loan_id).into())
+    }
+
+    /// Permanently removes an active or archived loan. Its ID is not reused.
+    pub fn delete_loan(&mut self, loan_id: u32) -> Result<Loan, Error> {
+        let loan = self
+            .loans
+            .remove(&loan_id)
+            .or_else(|| self.archived_loans.remove(&loan_id))
+            .ok_or(LoanNotFound(loan_id))?;
+        self.record_version(loan_id, Amendment::Deleted, None, &loan)?;
+        self.save()?;
+        Ok(loan)
+    }
+
+    /// Moves a loan out of the active book, it can be brought back with `restore_loan`.
+    pub fn archive_loan(&mut self, loan_id: u32) -> Result<(), Error> {
+        let loan = self.loans.remove(&loan_id).ok_or(LoanNotFound(loan_id))?;
+        self.record_version(loan_id, Amendment::Archived, None, &loan)?;
+        self.archived_loans.insert(loan_id, loan);
+        self.save()
+    }
+
+    /// Returns an archived loan to the active book under its original ID.
+    pub fn restore_loan(&mut self, loan_id: u32) -> Result<(), Error> {
+        if self.loans.contains_key(&loan_id) {
+            return Err(anyhow!("Loan with ID {} is not archived.", loan_id));
+        }
+        let loan = self.archived_loans.remove(&loan_id).ok_or(LoanNotFound(loan_id))?;
+        self.record_version(loan_id, Amendment::Restored, None, &loan)?;
+        self.loans.insert(loan_id, loan);
+        self.save()
+    }
+
+    pub fn loan_history(&self, loan_id: u32) -> Result<&[LoanVersion], Error> {
+        self.history
+            .get(&loan_id)
+            .map(Vec::as_slice)
+            .or_else(|| (self.loans.contains_key(&loan_id) || self.archived_loans.contains_key(&loan_id)).then_some(&[][..]))
+            .ok_or(LoanNotFound(loan_id).into())
+    }
+
+    /// Recalculates a historical version of a loan with the current market data, for reconciling
+    /// what was charged under the terms in force at the time.
+    pub fn loan_as_of_version(&self, loan_id: u32, version: u32) -> Result<Loan, Error> {
+        let mut loan = self
+            .loan_history(loan_id)?
+            .iter()
+            .find(|loan_version| loan_version.version == version)
+            .ok_or(anyhow!("Loan with ID {} has no version {}.", loan_id, version))?
+            .loan
+            .clone();
+        loan.calculate_interest(&self.market_data)?;
+        Ok(loan)
+    }
+
+    /// Loads benchmark fixings from a CSV file and recalculates every loan that references an index.
+    pub fn load_fixings(&mut self, path: &Path) -> Result<MarketDataLoad, Error> {
+        let loaded = self.market_data.fixings.load_csv(path)?;
+        let failed_loans = self.recalculate_loans(|loan| loan.rate_index.is_some())?;
+        Ok(MarketDataLoad { loaded, failed_loans })
+    }
+
+    /// Loads FX rates from a CSV file, returning the number of rates loaded. FX rates are only used for
+    /// portfolio totals so no loans need recalculating.
+    pub fn load_fx_rates(&mut self, path: &Path) -> Result<usize, Error> {
+        self.market_data.fx_rates.load_csv(path)
+    }
+
+    /// Loads a holiday calendar from a CSV file and recalculates every loan that uses a business day calendar.
+    pub fn load_calendar(&mut self, name: &str, path: &Path) -> Result<MarketDataLoad, Error> {
+        let loaded = self.market_data.calendars.load_file(name, path)?;
+        let failed_loans = self.recalculate_loans(|loan| !loan.business_day_calendars.is_empty())?;
+        Ok(MarketDataLoad { loaded, failed_loans })
+    }
+
+    /// Recalculates the active loans matching `filter`, returning the IDs of those that failed with the reason.
+    fn recalculate_loans(&mut self, filter: impl Fn(&Loan) -> bool) -> Result<Vec<(u32, Error)>, Error> {
+        let mut failed_loans = Vec::new();
+        for (loan_id, loan) in self.loans.iter_mut().filter(|(_, loan)| filter(loan)) {
+            if let Err(e) = loan.calculate_interest(&self.market_data) {
+                failed_loans.push((*loan_id, e));
+            }
+        }
+        self.save()?;
+        Ok(failed_loans)
+    }
+}
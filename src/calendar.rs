@@ -0,0 +1,203 @@
+use anyhow::{anyhow, Context, Error};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Holidays of a market or currency. Saturdays and Sundays are never business days.
+#[derive(Clone, Debug, Default)]
+pub struct HolidayCalendar {
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl HolidayCalendar {
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.holidays.contains(&date)
+    }
+
+    /// Adds the holidays of `other`, so a date is only a business day if it is one in both calendars.
+    pub fn join(&mut self, other: &HolidayCalendar) {
+        self.holidays.extend(other.holidays.iter().copied());
+    }
+}
+
+/// Holiday calendars keyed by name, e.g. TARGET, GBLO or USNY.
+#[derive(Clone, Debug, Default)]
+pub struct Calendars {
+    // names are stored upper case so lookups are case insensitive
+    calendars: BTreeMap<String, HolidayCalendar>,
+}
+
+impl Calendars {
+    /// Loads a calendar from a CSV file with the header `date,description`, replacing any calendar with the same name.
+    /// Returns the number of holidays loaded.
+    pub fn load_file(&mut self, name: &str, path: &Path) -> Result<usize, Error> {
+        let contents = fs::read_to_string(path).with_context(|| format!("Could not read {}", path.display()))?;
+        let mut calendar = HolidayCalendar::default();
+        for (line_number, line) in contents.lines().enumerate().skip(1) {
+            let Some(date) = line.split(',').next().map(str::trim).filter(|date| !date.is_empty()) else {
+                continue;
+            };
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").with_context(|| format!("Line {}: invalid date", line_number + 1))?;
+            calendar.holidays.insert(date);
+        }
+        let loaded = calendar.holidays.len();
+        self.calendars.insert(name.trim().to_uppercase(), calendar);
+        Ok(loaded)
+    }
+
+    /// Returns the joint calendar of all the named calendars, no names gives a weekends only calendar.
+    pub fn joint(&self, names: &[String]) -> Result<HolidayCalendar, Error> {
+        let mut joint = HolidayCalendar::default();
+        for name in names {
+            let calendar = self
+                .calendars
+                .get(&name.trim().to_uppercase())
+                .ok_or(anyhow!("Holiday calendar {} has not been loaded.", name))?;
+            joint.join(calendar);
+        }
+        Ok(joint)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = (&String, usize)> {
+        self.calendars.iter().map(|(name, calendar)| (name, calendar.holidays.len()))
+    }
+}
+
+/// How a schedule date falling on a non business day is moved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RollConvention {
+    Following,
+    /// Following unless that moves into the next month, in which case Preceding.
+    ModifiedFollowing,
+    Preceding,
+    /// Preceding unless that moves into the previous month, in which case Following.
+    ModifiedPreceding,
+    Unadjusted,
+}
+
+impl RollConvention {
+    pub fn adjust(&self, date: NaiveDate, calendar: &HolidayCalendar) -> NaiveDate {
+        match self {
+            RollConvention::Unadjusted => date,
+            RollConvention::Following => roll(date, calendar, 1),
+            RollConvention::Preceding => roll(date, calendar, -1),
+            RollConvention::ModifiedFollowing => {
+                let following = roll(date, calendar, 1);
+                if following.month() == date.month() {
+                    following
+                } else {
+                    roll(date, calendar, -1)
+                }
+            }
+            RollConvention::ModifiedPreceding => {
+                let preceding = roll(date, calendar, -1);
+                if preceding.month() == date.month() {
+                    preceding
+                } else {
+                    roll(date, calendar, 1)
+                }
+            }
+        }
+    }
+}
+
+fn roll(mut date: NaiveDate, calendar: &HolidayCalendar, step: i64) -> NaiveDate {
+    while !calendar.is_business_day(date) {
+        date += Duration::days(step);
+    }
+    date
+}
+
+impl FromStr for RollConvention {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "following" => Ok(RollConvention::Following),
+            "modified-following" => Ok(RollConvention::ModifiedFollowing),
+            "preceding" => Ok(RollConvention::Preceding),
+            "modified-preceding" => Ok(RollConvention::ModifiedPreceding),
+            "unadjusted" => Ok(RollConvention::Unadjusted),
+            other => Err(anyhow!(
+                "Unknown roll convention '{}'. Expected following, modified-following, preceding, modified-preceding or unadjusted.",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for RollConvention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RollConvention::Following => "following",
+            RollConvention::ModifiedFollowing => "modified-following",
+            RollConvention::Preceding => "preceding",
+            RollConvention::ModifiedPreceding => "modified-preceding",
+            RollConvention::Unadjusted => "unadjusted",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::date;
+
+    fn christmas() -> HolidayCalendar {
+        HolidayCalendar {
+            holidays: BTreeSet::from([date(2024, 12, 25), date(2024, 12, 26)]),
+        }
+    }
+
+    #[test]
+    fn business_days_are_left_alone() {
+        let friday = date(2024, 8, 30);
+        for convention in [
+            RollConvention::Following,
+            RollConvention::ModifiedFollowing,
+            RollConvention::Preceding,
+            RollConvention::ModifiedPreceding,
+            RollConvention::Unadjusted,
+        ] {
+            assert_eq!(convention.adjust(friday, &HolidayCalendar::default()), friday);
+        }
+    }
+
+    #[test]
+    fn modified_following_rolls_back_across_a_month_end() {
+        // Saturday 31 August 2024, the following business day is Monday 2 September
+        let saturday = date(2024, 8, 31);
+        let weekends = HolidayCalendar::default();
+        assert_eq!(RollConvention::Following.adjust(saturday, &weekends), date(2024, 9, 2));
+        assert_eq!(RollConvention::ModifiedFollowing.adjust(saturday, &weekends), date(2024, 8, 30));
+        // within the month modified following is the same as following
+        assert_eq!(RollConvention::ModifiedFollowing.adjust(date(2024, 8, 24), &weekends), date(2024, 8, 26));
+    }
+
+    #[test]
+    fn preceding_rolls_back_across_a_month_start() {
+        // Saturday 1 June 2024, the preceding business day is Friday 31 May
+        let saturday = date(2024, 6, 1);
+        let weekends = HolidayCalendar::default();
+        assert_eq!(RollConvention::Preceding.adjust(saturday, &weekends), date(2024, 5, 31));
+        assert_eq!(RollConvention::ModifiedPreceding.adjust(saturday, &weekends), date(2024, 6, 3));
+        assert_eq!(RollConvention::Unadjusted.adjust(saturday, &weekends), saturday);
+    }
+
+    #[test]
+    fn holidays_are_skipped_with_weekends() {
+        let calendar = christmas();
+        assert_eq!(RollConvention::Following.adjust(date(2024, 12, 25), &calendar), date(2024, 12, 27));
+        assert_eq!(RollConvention::Preceding.adjust(date(2024, 12, 26), &calendar), date(2024, 12, 24));
+
+        let mut joint = HolidayCalendar::default();
+        joint.join(&calendar);
+        assert!(!joint.is_business_day(date(2024, 12, 26)));
+        assert!(joint.is_business_day(date(2024, 12, 27)));
+    }
+}
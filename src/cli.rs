@@ -0,0 +1,526 @@
+use anyhow::{anyhow, Error};
+use chrono::{Local, NaiveDate};
+use clap::{Args, Parser, Subcommand};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::fmt;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use interest_app::calendar::RollConvention;
+use interest_app::compounding::Compounding;
+use interest_app::currency::Currency;
+use interest_app::day_count::DayCountConvention;
+use interest_app::export::{self, ExportFormat};
+use interest_app::facility::{self, Facility};
+use interest_app::history;
+use interest_app::fixings::FixingFallback;
+use interest_app::import;
+use interest_app::overdue;
+use interest_app::payoff::BreakCost;
+use interest_app::portfolio;
+use interest_app::query::{self, LoanFilter, LoanStatus, SortKey};
+use interest_app::repayment::{PaymentFrequency, RepaymentType};
+use interest_app::schedule::{self, StubType};
+use interest_app::server;
+use interest_app::storage;
+use interest_app::validation::ValidationErrors;
+use interest_app::report::{self, Granularity, ReportColumn, ReportOptions};
+use interest_app::{parse_dated_values, Loan, LoanCalculator, LoanNotFound};
+use interest_app::RoundingPolicy;
+
+use crate::display;
+
+/// Loan interest calculator. Runs the interactive menu when no command is given.
+#[derive(Debug, Parser)]
+#[command(version)]
+pub struct Cli {
+    /// JSON file the loan book is stored in.
+    #[arg(long, global = true, env = storage::BOOK_PATH_VARIABLE, default_value = storage::DEFAULT_BOOK_PATH)]
+    pub book: PathBuf,
+
+    /// CSV file of benchmark fixings (index,date,rate%) to load before running the command. Can be repeated.
+    #[arg(long, global = true)]
+    pub fixings: Vec<PathBuf>,
+
+    /// CSV file of FX rates (date,base,quote,rate) to load before running the command. Can be repeated.
+    #[arg(long, global = true)]
+    pub fx_rates: Vec<PathBuf>,
+
+    /// Holiday calendar to load before running the command, as NAME=FILE. Can be repeated.
+    #[arg(long, global = true)]
+    pub calendar: Vec<String>,
+
+    /// Name recorded in the history of loans amended by this run, defaults to the login name.
+    #[arg(long, global = true, env = history::USER_VARIABLE)]
+    pub user: Option<String>,
+
+    /// Accept negative base rates, margins and fallback rates, down to -10%.
+    #[arg(long, global = true, env = "INTEREST_APP_ALLOW_NEGATIVE_RATES")]
+    pub allow_negative_rates: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Add a loan and print its ID.
+    #[command(
+        mut_arg("start", |arg| arg.required(true)),
+        mut_arg("end", |arg| arg.required(true)),
+        mut_arg("amount", |arg| arg.required(true)),
+        mut_arg("ccy", |arg| arg.required(true)),
+        mut_arg("base", |arg| arg.required(true)),
+        mut_arg("margin", |arg| arg.required(true))
+    )]
+    Add(LoanTerms),
+    /// Change the terms of an existing loan, anything not given is left as it is.
+    Update {
+        id: u32,
+        #[command(flatten)]
+        terms: LoanTerms,
+    },
+    /// Show the calculated interest of a loan.
+    Show {
+        id: u32,
+        /// daily or monthly accrual rows.
+        #[arg(long, default_value = "daily")]
+        granularity: Granularity,
+        /// Columns to show, comma separated: base-rate, principal, interest, interest-excl-margin, commitment-fee, default-interest, balance.
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<ReportColumn>,
+        /// Add a subtotal row after each month of daily accruals.
+        #[arg(long)]
+        subtotals: bool,
+        #[arg(long)]
+        page_size: Option<usize>,
+        /// Page to show, starting at 1. Needs --page-size.
+        #[arg(long, requires = "page_size")]
+        page: Option<usize>,
+        /// Recalculate the loan under the terms of an earlier version from its history.
+        #[arg(long)]
+        as_of_version: Option<u32>,
+    },
+    /// Show principal and interest totals per currency and converted into a reporting currency.
+    Portfolio {
+        /// Reporting currency the totals are converted into.
+        #[arg(long)]
+        currency: Currency,
+        /// Date of the FX rates to use, defaults to today. The latest rate on or before the date is used.
+        #[arg(long)]
+        date: Option<NaiveDate>,
+    },
+    /// Show the interest accrued from the start date, or from --from, up to a date.
+    Accrued {
+        id: u32,
+        /// Start of the period, defaults to the loan's start date.
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// End of the period, defaults to today.
+        #[arg(long)]
+        to: Option<NaiveDate>,
+    },
+    /// Quote the amount needed to repay a loan in full on a date.
+    Payoff {
+        id: u32,
+        /// Payoff date, defaults to today.
+        #[arg(long)]
+        date: Option<NaiveDate>,
+        /// none, percentage=<rate%> of the principal, fee=<amount> or make-whole=<reinvestment rate%>.
+        #[arg(long, default_value = "none")]
+        break_cost: BreakCost,
+    },
+    /// Find active and archived loans, sort them and show statistics per currency.
+    Search {
+        #[arg(long)]
+        currency: Option<Currency>,
+        /// Only loans whose term overlaps the period from this date.
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Only loans whose term overlaps the period up to this date.
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        #[arg(long)]
+        min_amount: Option<Decimal>,
+        #[arg(long)]
+        max_amount: Option<Decimal>,
+        /// Minimum all-in rate in %.
+        #[arg(long, allow_negative_numbers = true)]
+        min_rate: Option<Decimal>,
+        /// Maximum all-in rate in %.
+        #[arg(long, allow_negative_numbers = true)]
+        max_rate: Option<Decimal>,
+        /// active, matured or archived.
+        #[arg(long)]
+        status: Option<LoanStatus>,
+        /// id, status, currency, amount, start-date, end-date, rate, interest or remaining-life.
+        #[arg(long, default_value = "id")]
+        sort: SortKey,
+        #[arg(long)]
+        descending: bool,
+        /// Date statuses and remaining lives are measured on, defaults to today.
+        #[arg(long)]
+        as_of: Option<NaiveDate>,
+    },
+    /// Show every recorded version of a loan with the fields that changed.
+    History { id: u32 },
+    /// Serve the loan book as a JSON REST API on localhost.
+    Serve {
+        #[arg(long, default_value_t = server::DEFAULT_PORT)]
+        port: u16,
+    },
+    /// Show a summary of every loan.
+    List {
+        /// Show the archived loans instead of the active ones.
+        #[arg(long)]
+        archived: bool,
+    },
+    /// Permanently delete an active or archived loan. Its ID is never reused.
+    Delete { id: u32 },
+    /// Archive a loan, hiding it from listings while keeping it for audit.
+    Archive { id: u32 },
+    /// Return an archived loan to the active book.
+    Restore { id: u32 },
+    /// Import loans from a CSV file with the header start_date,end_date,amount,currency,base_rate,margin[,day_count][,compounding].
+    Import { file: PathBuf },
+    /// Show the amortization schedule of a loan.
+    Schedule {
+        id: u32,
+        #[arg(long, default_value = "monthly")]
+        frequency: PaymentFrequency,
+        #[arg(long, default_value = "short-back")]
+        stub: StubType,
+    },
+    /// Export the daily accruals of one loan, or of every loan when no ID is given.
+    Export {
+        id: Option<u32>,
+        #[arg(long)]
+        output: PathBuf,
+        /// csv or json, taken from the output file's extension when not given.
+        #[arg(long)]
+        format: Option<ExportFormat>,
+    },
+}
+
+/// Loan parameters, rates are entered in % like the interactive menu.
+#[derive(Debug, Args)]
+pub struct LoanTerms {
+    #[arg(long)]
+    start: Option<NaiveDate>,
+    #[arg(long)]
+    end: Option<NaiveDate>,
+    #[arg(long, allow_negative_numbers = true)]
+    amount: Option<Decimal>,
+    /// ISO 4217 currency code, also sets the day count to the currency's market default unless --day-count is given.
+    #[arg(long)]
+    ccy: Option<Currency>,
+    /// Base interest rate in %.
+    #[arg(long, allow_negative_numbers = true)]
+    base: Option<Decimal>,
+    /// Base rate resets as YYYY-MM-DD=rate%, comma separated.
+    #[arg(long)]
+    base_resets: Option<String>,
+    /// Benchmark index whose fixings replace the base rate, empty to stop using one.
+    #[arg(long)]
+    index: Option<String>,
+    /// previous, error or fixed=<rate%>.
+    #[arg(long)]
+    fixing_fallback: Option<FixingFallback>,
+    /// Margin in %.
+    #[arg(long, allow_negative_numbers = true)]
+    margin: Option<Decimal>,
+    #[arg(long)]
+    day_count: Option<DayCountConvention>,
+    #[arg(long)]
+    compounding: Option<Compounding>,
+    #[arg(long)]
+    repayment: Option<RepaymentType>,
+    #[arg(long)]
+    repayment_frequency: Option<PaymentFrequency>,
+    #[arg(long)]
+    stub: Option<StubType>,
+    /// How often interest is paid, by default with scheduled repayments or at maturity.
+    #[arg(long)]
+    interest_frequency: Option<PaymentFrequency>,
+    /// Custom repayments as YYYY-MM-DD=amount, comma separated.
+    #[arg(long)]
+    repayments: Option<String>,
+    #[arg(long)]
+    balloon: Option<Decimal>,
+    /// Business day calendars, comma separated.
+    #[arg(long)]
+    calendars: Option<String>,
+    #[arg(long)]
+    roll: Option<RollConvention>,
+    #[arg(long)]
+    facility_limit: Option<Decimal>,
+    /// Commitment fee on the undrawn amount in %.
+    #[arg(long)]
+    commitment_fee: Option<Decimal>,
+    /// Drawdowns and repayments as YYYY-MM-DD=+amount or YYYY-MM-DD=-amount, comma separated.
+    #[arg(long, allow_hyphen_values = true)]
+    ledger: Option<String>,
+    /// Additional margin in % charged on overdue payments.
+    #[arg(long)]
+    default_margin: Option<Decimal>,
+    /// Missed payments as YYYY-MM-DD=amount, or YYYY-MM-DD=amount:YYYY-MM-DD once paid, comma separated.
+    #[arg(long)]
+    overdue: Option<String>,
+    #[arg(long)]
+    rounding: Option<RoundingPolicy>,
+}
+
+impl LoanTerms {
+    /// Overwrites the loan's parameters with every term that was given.
+    fn apply(&self, loan: &mut Loan) -> Result<(), Error> {
+        if let Some(start) = self.start {
+            loan.start_date = start;
+        }
+        if let Some(end) = self.end {
+            loan.end_date = end;
+        }
+        if let Some(amount) = self.amount {
+            loan.loan_amount = amount;
+        }
+        if let Some(ccy) = self.ccy {
+            loan.loan_currency = ccy;
+            loan.day_count_convention = ccy.default_day_count();
+        }
+        // divide by 100 to convert to %
+        if let Some(base) = self.base {
+            loan.base_interest_rate = base / dec!(100);
+        }
+        if let Some(base_resets) = &self.base_resets {
+            loan.base_rate_schedule = parse_dated_values(base_resets)?
+                .into_iter()
+                .map(|(date, rate)| (date, rate / dec!(100)))
+                .collect();
+        }
+        if let Some(index) = &self.index {
+            loan.rate_index = Some(index.trim().to_uppercase()).filter(|index| !index.is_empty());
+        }
+        if let Some(fixing_fallback) = self.fixing_fallback {
+            loan.fixing_fallback = fixing_fallback;
+        }
+        if let Some(margin) = self.margin {
+            loan.margin = margin / dec!(100);
+        }
+        if let Some(day_count) = self.day_count {
+            loan.day_count_convention = day_count;
+        }
+        if let Some(compounding) = self.compounding {
+            loan.compounding = compounding;
+        }
+        if let Some(repayment) = self.repayment {
+            loan.repayment_schedule.repayment_type = repayment;
+        }
+        if let Some(frequency) = self.repayment_frequency {
+            loan.repayment_schedule.frequency = frequency;
+        }
+        if let Some(stub) = self.stub {
+            loan.repayment_schedule.stub = stub;
+        }
+        if let Some(frequency) = self.interest_frequency {
+            loan.interest_frequency = Some(frequency);
+        }
+        if let Some(repayments) = &self.repayments {
+            loan.repayment_schedule.custom_repayments = parse_dated_values(repayments)?;
+        }
+        if let Some(balloon) = self.balloon {
+            loan.repayment_schedule.balloon_amount = balloon;
+        }
+        if let Some(calendars) = &self.calendars {
+            loan.business_day_calendars = calendars
+                .split(',')
+                .map(|name| name.trim().to_uppercase())
+                .filter(|name| !name.is_empty())
+                .collect();
+        }
+        if let Some(roll) = self.roll {
+            loan.roll_convention = roll;
+        }
+        if let Some(limit) = self.facility_limit {
+            let commitment_fee_rate = loan.facility.map_or(Decimal::ZERO, |facility| facility.commitment_fee_rate);
+            loan.facility = Some(Facility { limit, commitment_fee_rate });
+        }
+        if let Some(commitment_fee) = self.commitment_fee {
+            let facility = loan
+                .facility
+                .as_mut()
+                .ok_or(anyhow!("A commitment fee needs a facility limit."))?;
+            facility.commitment_fee_rate = commitment_fee / dec!(100);
+        }
+        if let Some(ledger) = &self.ledger {
+            loan.ledger = facility::parse_ledger(ledger)?;
+        }
+        if let Some(default_margin) = self.default_margin {
+            // divide by 100 to convert to %
+            loan.default_margin = default_margin / dec!(100);
+        }
+        if let Some(overdue) = &self.overdue {
+            loan.overdue_events = overdue::parse_overdue(overdue)?;
+        }
+        if let Some(rounding) = self.rounding {
+            loan.rounding_policy = rounding;
+        }
+        Ok(())
+    }
+}
+
+/// Returned when some rows of an import were rejected.
+#[derive(Debug)]
+pub struct RowsRejected(pub usize);
+
+impl fmt::Display for RowsRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} rows could not be imported.", self.0)
+    }
+}
+
+impl std::error::Error for RowsRejected {}
+
+/// Process exit codes so batch jobs can tell failures apart.
+pub fn exit_code(error: &Error) -> ExitCode {
+    if error.is::<LoanNotFound>() {
+        ExitCode::from(3)
+    } else if error.is::<RowsRejected>() {
+        ExitCode::from(4)
+    } else if error.is::<ValidationErrors>() {
+        ExitCode::from(5)
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+pub fn run_command(command: Command, calculator: &mut LoanCalculator) -> Result<(), Error> {
+    match command {
+        Command::Add(terms) => {
+            let mut loan = Loan::new();
+            terms.apply(&mut loan)?;
+            let loan_id = calculator.add_loan(loan)?;
+            println!("{}", loan_id);
+        }
+        Command::Update { id, terms } => {
+            let mut loan = calculator.get_loan(id)?.clone();
+            terms.apply(&mut loan)?;
+            calculator.update_loan(id, loan)?;
+            println!("Loan with ID {} updated successfully!\n", id);
+        }
+        Command::Show { id, granularity, columns, subtotals, page_size, page, as_of_version } => {
+            let options = ReportOptions {
+                granularity,
+                columns: if columns.is_empty() { ReportColumn::ALL.to_vec() } else { columns },
+                page_size,
+                page,
+                monthly_subtotals: subtotals,
+            };
+            match as_of_version {
+                Some(version) => {
+                    let loan = calculator.loan_as_of_version(id, version)?;
+                    println!("Loan ID {} as of version {}", id, version);
+                    display::print_report(&loan, &options)?;
+                }
+                None => display::print_report(calculator.get_loan(id)?, &options)?,
+            }
+        }
+        Command::Portfolio { currency, date } => {
+            let date = date.unwrap_or_else(|| Local::now().date_naive());
+            let summary = portfolio::summarise(calculator.loans().values(), currency, date, &calculator.market_data().fx_rates)?;
+            display::print_portfolio(&summary);
+        }
+        Command::Accrued { id, from, to } => {
+            let loan = calculator.get_loan(id)?;
+            let to = to.unwrap_or_else(|| Local::now().date_naive());
+            let accrued = match from {
+                Some(from) => loan.accrued_between(from, to)?,
+                None => loan.accrued_to(to),
+            };
+            display::print_accrued(loan, from.unwrap_or(loan.start_date), to, &accrued);
+        }
+        Command::Payoff { id, date, break_cost } => {
+            let loan = calculator.get_loan(id)?;
+            let date = date.unwrap_or_else(|| Local::now().date_naive());
+            display::print_payoff_quote(loan, &loan.payoff_quote(date, break_cost, calculator.market_data())?);
+        }
+        Command::Search {
+            currency,
+            from,
+            to,
+            min_amount,
+            max_amount,
+            min_rate,
+            max_rate,
+            status,
+            sort,
+            descending,
+            as_of,
+        } => {
+            // divide by 100 to convert to %
+            let filter = LoanFilter {
+                currency,
+                from,
+                to,
+                min_amount,
+                max_amount,
+                min_rate: min_rate.map(|rate| rate / dec!(100)),
+                max_rate: max_rate.map(|rate| rate / dec!(100)),
+                status,
+            };
+            let as_of = as_of.unwrap_or_else(|| Local::now().date_naive());
+            let results = query::query(calculator, &filter, sort, descending, as_of);
+            display::print_query_results(&results);
+        }
+        Command::History { id } => display::print_history(id, calculator.loan_history(id)?),
+        Command::Serve { port } => {
+            let server = server::LoanServer::bind(port)?;
+            println!("Listening on http://{}", server.address());
+            server.serve(calculator);
+        }
+        Command::List { archived } => {
+            let loans = if archived { calculator.archived_loans() } else { calculator.loans() };
+            for (loan_id, loan) in loans.iter() {
+                println!("Loan ID: {}", loan_id);
+                for line in report::summary(loan) {
+                    println!("{}", line);
+                }
+                println!();
+            }
+        }
+        Command::Delete { id } => {
+            calculator.delete_loan(id)?;
+            println!("Loan with ID {} deleted.", id);
+        }
+        Command::Archive { id } => {
+            calculator.archive_loan(id)?;
+            println!("Loan with ID {} archived.", id);
+        }
+        Command::Restore { id } => {
+            calculator.restore_loan(id)?;
+            println!("Loan with ID {} restored.", id);
+        }
+        Command::Import { file } => {
+            let report = import::import_csv(&file, calculator)?;
+            display::print_import_report(&report);
+            if !report.rejected.is_empty() {
+                return Err(RowsRejected(report.rejected.len()).into());
+            }
+        }
+        Command::Schedule { id, frequency, stub } => {
+            let loan = calculator.get_loan(id)?;
+            let calendar = calculator.market_data().calendars.joint(&loan.business_day_calendars)?;
+            let periods = schedule::amortization_schedule(loan, frequency, stub, &calendar);
+            display::print_amortization_schedule(loan, &periods);
+        }
+        Command::Export { id, output, format } => {
+            let format = format
+                .or_else(|| ExportFormat::from_path(&output))
+                .ok_or(anyhow!("Give --format csv or json, or an output file ending in .csv or .json."))?;
+            match id {
+                Some(id) => export::export_loans(std::iter::once((id, calculator.get_loan(id)?)), format, &output)?,
+                None => export::export_loans(calculator.loans().iter().map(|(loan_id, loan)| (*loan_id, loan)), format, &output)?,
+            }
+        }
+    }
+    Ok(())
+}
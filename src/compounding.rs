@@ -0,0 +1,253 @@
+use anyhow::{anyhow, Error};
+use chrono::{Months, NaiveDate};
+use rust_decimal::{Decimal, MathematicalOps};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How accrued interest is rolled into the balance that later interest is charged on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Compounding {
+    /// Interest is always charged on the principal, nothing is capitalised.
+    Simple,
+    Daily,
+    Monthly,
+    Quarterly,
+    Annual,
+    /// Interest is capitalised continuously, i.e. balance * e^(rate * year fraction).
+    Continuous,
+    /// The base rate is compounded daily in arrears (SOFR/SONIA style) while the margin accrues simply on the principal.
+    InArrears,
+}
+
+impl Compounding {
+    pub const ALL: [Compounding; 7] = [
+        Compounding::Simple,
+        Compounding::Daily,
+        Compounding::Monthly,
+        Compounding::Quarterly,
+        Compounding::Annual,
+        Compounding::Continuous,
+        Compounding::InArrears,
+    ];
+
+    /// Number of months between capitalisation dates for the periodic compounding modes.
+    fn period_months(&self) -> Option<u32> {
+        match self {
+            Compounding::Monthly => Some(1),
+            Compounding::Quarterly => Some(3),
+            Compounding::Annual => Some(12),
+            _ => None,
+        }
+    }
+}
+
+/// Running state of a loan's balance while interest is accrued day by day.
+#[derive(Clone, Debug)]
+pub struct CompoundingAccrual {
+    compounding: Compounding,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    principal: Decimal,
+    /// Balance interest is currently charged on, i.e. principal plus any capitalised interest.
+    balance: Decimal,
+    /// Interest accrued since the last capitalisation date.
+    uncapitalised: Decimal,
+    periods_capitalised: u32,
+}
+
+impl CompoundingAccrual {
+    pub fn new(compounding: Compounding, principal: Decimal, start_date: NaiveDate, end_date: NaiveDate) -> Self {
+        CompoundingAccrual {
+            compounding,
+            start_date,
+            end_date,
+            principal,
+            balance: principal,
+            uncapitalised: Decimal::ZERO,
+            periods_capitalised: 0,
+        }
+    }
+
+    /// Accrues a single day ending on `date` and returns (interest, interest excluding margin) for that day.
+    pub fn accrue(&mut self, date: NaiveDate, base_rate: Decimal, margin: Decimal, day_fraction: Decimal) -> (Decimal, Decimal) {
+        let total_rate = base_rate + margin;
+        let (interest, interest_no_margin) = match self.compounding {
+            Compounding::Simple => (
+                self.principal * total_rate * day_fraction,
+                self.principal * base_rate * day_fraction,
+            ),
+            Compounding::Continuous => (
+                self.balance * ((total_rate * day_fraction).exp() - Decimal::ONE),
+                self.balance * ((base_rate * day_fraction).exp() - Decimal::ONE),
+            ),
+            Compounding::InArrears => {
+                // only the base rate compounds, the margin is added on a simple basis
+                let base_interest = self.balance * base_rate * day_fraction;
+                let margin_interest = self.principal * margin * day_fraction;
+                (base_interest + margin_interest, base_interest)
+            }
+            Compounding::Daily | Compounding::Monthly | Compounding::Quarterly | Compounding::Annual => (
+                self.balance * total_rate * day_fraction,
+                self.balance * base_rate * day_fraction,
+            ),
+        };
+
+        match self.compounding {
+            Compounding::Simple => {}
+            Compounding::InArrears => self.balance += interest_no_margin,
+            Compounding::Daily | Compounding::Continuous => self.balance += interest,
+            Compounding::Monthly | Compounding::Quarterly | Compounding::Annual => {
+                self.uncapitalised += interest;
+                if self.is_capitalisation_date(date) {
+                    self.balance += self.uncapitalised;
+                    self.uncapitalised = Decimal::ZERO;
+                }
+            }
+        }
+        (interest, interest_no_margin)
+    }
+
+    /// Moves the principal and the balance interest is charged on, positive for drawdowns and negative for repayments.
+    pub fn change_principal(&mut self, amount: Decimal) {
+        self.principal += amount;
+        self.balance += amount;
+    }
+
+    pub fn principal(&self) -> Decimal {
+        self.principal
+    }
+
+    /// The balance interest is charged on after any capitalisation on the last accrued day.
+    pub fn capitalised_balance(&self) -> Decimal {
+        self.balance
+    }
+
+    fn is_capitalisation_date(&mut self, date: NaiveDate) -> bool {
+        if date >= self.end_date {
+            return true;
+        }
+        let Some(months) = self.compounding.period_months() else {
+            return false;
+        };
+        // capitalisation dates are measured from the start date so month ends do not drift
+        let next_date = self
+            .start_date
+            .checked_add_months(Months::new(months * (self.periods_capitalised + 1)))
+            .unwrap();
+        if date >= next_date {
+            self.periods_capitalised += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl FromStr for Compounding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalised = s.trim().to_lowercase();
+        Compounding::ALL
+            .iter()
+            .find(|compounding| compounding.to_string() == normalised)
+            .copied()
+            .ok_or(anyhow!(
+                "Unknown compounding '{}'. Expected one of {}.",
+                s.trim(),
+                Compounding::ALL.map(|compounding| compounding.to_string()).join(", ")
+            ))
+    }
+}
+
+impl fmt::Display for Compounding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Compounding::Simple => "simple",
+            Compounding::Daily => "daily",
+            Compounding::Monthly => "monthly",
+            Compounding::Quarterly => "quarterly",
+            Compounding::Annual => "annual",
+            Compounding::Continuous => "continuous",
+            Compounding::InArrears => "in-arrears",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::date;
+    use rust_decimal_macros::dec;
+
+    /// Accrues every day after `start` up to and including `end` on ACT/365, returning the total (interest, interest excluding margin).
+    fn accrue_all(accrual: &mut CompoundingAccrual, start: NaiveDate, end: NaiveDate, base_rate: Decimal, margin: Decimal) -> (Decimal, Decimal) {
+        let day_fraction = Decimal::ONE / dec!(365);
+        let mut totals = (Decimal::ZERO, Decimal::ZERO);
+        for date in start.iter_days().skip(1).take_while(|date| *date <= end) {
+            let (interest, interest_no_margin) = accrual.accrue(date, base_rate, margin, day_fraction);
+            totals.0 += interest;
+            totals.1 += interest_no_margin;
+        }
+        totals
+    }
+
+    #[test]
+    fn daily_compounding_matches_the_compounded_rate() {
+        let (start, end) = (date(2023, 1, 1), date(2024, 1, 1));
+        let mut accrual = CompoundingAccrual::new(Compounding::Daily, dec!(1000000), start, end);
+        // 1,000,000 * ((1 + 3.65% / 365)^365 - 1)
+        let (interest, _) = accrue_all(&mut accrual, start, end, dec!(0.0265), dec!(0.01));
+        assert_eq!(interest.round_dp(2), dec!(37172.41));
+        assert_eq!(accrual.capitalised_balance().round_dp(2), dec!(1037172.41));
+        assert_eq!(accrual.principal(), dec!(1000000));
+    }
+
+    #[test]
+    fn monthly_capitalisation_keeps_to_month_ends() {
+        let (start, end) = (date(2024, 1, 31), date(2024, 4, 30));
+        let mut accrual = CompoundingAccrual::new(Compounding::Monthly, dec!(1000000), start, end);
+        let mut capitalised = Vec::new();
+        let mut balance = accrual.capitalised_balance();
+        for date in start.iter_days().skip(1).take_while(|date| *date <= end) {
+            accrual.accrue(date, dec!(0.12), Decimal::ZERO, Decimal::ONE / dec!(365));
+            if accrual.capitalised_balance() != balance {
+                balance = accrual.capitalised_balance();
+                capitalised.push((date, balance.round_dp(2)));
+            }
+        }
+        // Jan 31 + 1 month is Feb 29, then Mar 31 rather than Mar 29
+        assert_eq!(
+            capitalised,
+            [
+                (date(2024, 2, 29), dec!(1009534.25)),
+                (date(2024, 3, 31), dec!(1019823.20)),
+                (date(2024, 4, 30), dec!(1029881.73)),
+            ]
+        );
+    }
+
+    #[test]
+    fn continuous_compounding_matches_the_exponential() {
+        let (start, end) = (date(2023, 1, 1), date(2024, 1, 1));
+        let mut accrual = CompoundingAccrual::new(Compounding::Continuous, dec!(1000000), start, end);
+        // 1,000,000 * (e^5% - 1)
+        let (interest, interest_no_margin) = accrue_all(&mut accrual, start, end, dec!(0.05), Decimal::ZERO);
+        assert_eq!(interest.round_dp(2), dec!(51271.10));
+        assert_eq!(interest_no_margin.round_dp(2), dec!(51271.10));
+    }
+
+    #[test]
+    fn in_arrears_compounds_the_base_rate_but_not_the_margin() {
+        let (start, end) = (date(2023, 1, 1), date(2024, 1, 1));
+        let mut accrual = CompoundingAccrual::new(Compounding::InArrears, dec!(1000000), start, end);
+        let (interest, base_interest) = accrue_all(&mut accrual, start, end, dec!(0.04), dec!(0.01));
+        // 1,000,000 * ((1 + 4% / 365)^365 - 1) on the base rate and a simple 1% margin
+        assert_eq!(base_interest.round_dp(2), dec!(40808.49));
+        assert_eq!((interest - base_interest).round_dp(2), dec!(10000.00));
+        // only the base interest is added to the balance
+        assert_eq!(accrual.capitalised_balance().round_dp(2), dec!(1040808.49));
+    }
+}
@@ -0,0 +1,367 @@
+use anyhow::{anyhow, Error};
+use chrono::{Datelike, Months, NaiveDate};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Day count conventions used to turn a pair of dates into a fraction of a year.
+/// The names follow the ISDA 2006 definitions so they match what counterparties quote.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DayCountConvention {
+    /// Actual days / 365, leap years are ignored.
+    Act365Fixed,
+    /// Actual days / 360, common for money market loans.
+    Act360,
+    /// Actual days / 365 or 366 depending on whether the accrual period touches a leap day.
+    Act365L,
+    /// Days falling in a leap year / 366 plus days falling in a normal year / 365.
+    ActActIsda,
+    /// Actual days / (frequency * days in the reference coupon period).
+    ActActIcma,
+    /// 30/360 US (bond basis) including the end of February rules.
+    Thirty360Us,
+    /// 30E/360 (Eurobond basis).
+    ThirtyE360,
+    /// 30E/360 ISDA, month ends are treated as the 30th apart from a February maturity date.
+    ThirtyE360Isda,
+}
+
+/// The accrual period a year fraction is measured within.
+/// Only ACT/365L, ACT/ACT ICMA and 30E/360 ISDA need it, the other conventions only look at the two dates.
+#[derive(Clone, Copy, Debug)]
+pub struct AccrualPeriod {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Number of coupon periods per year.
+    pub frequency: u32,
+    /// Final date of the loan, 30E/360 ISDA keeps a February maturity at its actual day.
+    pub maturity: NaiveDate,
+}
+
+impl DayCountConvention {
+    pub const ALL: [DayCountConvention; 8] = [
+        DayCountConvention::Act365Fixed,
+        DayCountConvention::Act360,
+        DayCountConvention::Act365L,
+        DayCountConvention::ActActIsda,
+        DayCountConvention::ActActIcma,
+        DayCountConvention::Thirty360Us,
+        DayCountConvention::ThirtyE360,
+        DayCountConvention::ThirtyE360Isda,
+    ];
+
+    /// Returns the fraction of a year between `from` and `to` under this convention.
+    pub fn year_fraction(&self, from: NaiveDate, to: NaiveDate, period: &AccrualPeriod) -> Decimal {
+        if to <= from {
+            return Decimal::ZERO;
+        }
+        let actual_days = Decimal::from(to.signed_duration_since(from).num_days());
+        match self {
+            DayCountConvention::Act365Fixed => actual_days / dec!(365),
+            DayCountConvention::Act360 => actual_days / dec!(360),
+            DayCountConvention::Act365L => {
+                let leap = if period.frequency == 1 {
+                    contains_leap_day(period.start, period.end)
+                } else {
+                    is_leap_year(period.end.year())
+                };
+                actual_days / if leap { dec!(366) } else { dec!(365) }
+            }
+            DayCountConvention::ActActIsda => act_act_isda(from, to),
+            DayCountConvention::ActActIcma => act_act_icma(from, to, period),
+            DayCountConvention::Thirty360Us => {
+                let (mut d1, mut d2) = (from.day(), to.day());
+                if is_last_day_of_february(from) && is_last_day_of_february(to) {
+                    d2 = 30;
+                }
+                if is_last_day_of_february(from) {
+                    d1 = 30;
+                }
+                if d2 == 31 && d1 >= 30 {
+                    d2 = 30;
+                }
+                if d1 == 31 {
+                    d1 = 30;
+                }
+                thirty_360(from, to, d1, d2)
+            }
+            DayCountConvention::ThirtyE360 => thirty_360(from, to, from.day().min(30), to.day().min(30)),
+            DayCountConvention::ThirtyE360Isda => {
+                let d1 = if is_last_day_of_month(from) { 30 } else { from.day() };
+                let d2 = if is_last_day_of_month(to) && !(to == period.maturity && to.month() == 2) {
+                    30
+                } else {
+                    to.day()
+                };
+                thirty_360(from, to, d1, d2)
+            }
+        }
+    }
+
+    /// Returns the fraction of a year between `from` and `to` as the difference of the fractions from the start of
+    /// `period`. Fractions of consecutive parts of the period then add up to the fraction of the whole period, which
+    /// measuring each part on its own does not guarantee for the 30/360 conventions' month end rules.
+    pub fn accrual_fraction(&self, from: NaiveDate, to: NaiveDate, period: &AccrualPeriod) -> Decimal {
+        self.year_fraction(period.start, to, period) - self.year_fraction(period.start, from, period)
+    }
+
+    /// Returns the fraction of a year between `from` and `to` summed over the consecutive accrual `periods` they overlap,
+    /// each part measured within its own period. Days after the last period are measured within the last period.
+    pub fn fraction_over(&self, from: NaiveDate, to: NaiveDate, periods: &[AccrualPeriod]) -> Decimal {
+        periods
+            .iter()
+            .enumerate()
+            .map(|(index, period)| {
+                let start = from.max(period.start);
+                let end = if index + 1 == periods.len() { to } else { to.min(period.end) };
+                if end > start {
+                    self.accrual_fraction(start, end, period)
+                } else {
+                    Decimal::ZERO
+                }
+            })
+            .sum()
+    }
+}
+
+impl FromStr for DayCountConvention {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalised = s.trim().to_uppercase();
+        DayCountConvention::ALL
+            .iter()
+            .find(|convention| convention.to_string() == normalised)
+            .copied()
+            .ok_or(anyhow!(
+                "Unknown day count convention '{}'. Expected one of {}.",
+                s.trim(),
+                DayCountConvention::ALL.map(|convention| convention.to_string()).join(", ")
+            ))
+    }
+}
+
+impl fmt::Display for DayCountConvention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DayCountConvention::Act365Fixed => "ACT/365F",
+            DayCountConvention::Act360 => "ACT/360",
+            DayCountConvention::Act365L => "ACT/365L",
+            DayCountConvention::ActActIsda => "ACT/ACT ISDA",
+            DayCountConvention::ActActIcma => "ACT/ACT ICMA",
+            DayCountConvention::Thirty360Us => "30/360 US",
+            DayCountConvention::ThirtyE360 => "30E/360",
+            DayCountConvention::ThirtyE360Isda => "30E/360 ISDA",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+fn thirty_360(from: NaiveDate, to: NaiveDate, d1: u32, d2: u32) -> Decimal {
+    let days = 360 * (to.year() - from.year()) as i64
+        + 30 * (to.month() as i64 - from.month() as i64)
+        + (d2 as i64 - d1 as i64);
+    Decimal::from(days) / dec!(360)
+}
+
+fn act_act_isda(from: NaiveDate, to: NaiveDate) -> Decimal {
+    let mut fraction = Decimal::ZERO;
+    let mut current = from;
+    // split the period on calendar year boundaries so each part uses the length of its own year
+    while current < to {
+        let next_year = NaiveDate::from_ymd_opt(current.year() + 1, 1, 1).unwrap();
+        let part_end = next_year.min(to);
+        let days = Decimal::from(part_end.signed_duration_since(current).num_days());
+        fraction += days / Decimal::from(days_in_year(current.year()));
+        current = part_end;
+    }
+    fraction
+}
+
+fn act_act_icma(from: NaiveDate, to: NaiveDate, period: &AccrualPeriod) -> Decimal {
+    let frequency = period.frequency.max(1);
+    let months_per_period = 12 / frequency;
+    let mut fraction = Decimal::ZERO;
+    // notional coupon periods are rolled back from the end of the accrual period
+    let anchor = period.end.max(to);
+    let mut reference_end = anchor;
+    let mut step = 1;
+    while reference_end > from {
+        let reference_start = anchor.checked_sub_months(Months::new(months_per_period * step)).unwrap();
+        let overlap_start = from.max(reference_start);
+        let overlap_end = to.min(reference_end);
+        if overlap_end > overlap_start {
+            let overlap_days = Decimal::from(overlap_end.signed_duration_since(overlap_start).num_days());
+            let reference_days = Decimal::from(reference_end.signed_duration_since(reference_start).num_days());
+            fraction += overlap_days / (Decimal::from(frequency) * reference_days);
+        }
+        reference_end = reference_start;
+        step += 1;
+    }
+    fraction
+}
+
+fn contains_leap_day(start: NaiveDate, end: NaiveDate) -> bool {
+    (start.year()..=end.year())
+        .filter_map(|year| NaiveDate::from_ymd_opt(year, 2, 29))
+        .any(|leap_day| leap_day > start && leap_day <= end)
+}
+
+pub fn is_leap_year(year: i32) -> bool {
+    NaiveDate::from_ymd_opt(year, 2, 29).is_some()
+}
+
+fn days_in_year(year: i32) -> i64 {
+    if is_leap_year(year) {
+        366
+    } else {
+        365
+    }
+}
+
+pub fn is_last_day_of_month(date: NaiveDate) -> bool {
+    date.succ_opt().is_none_or(|next| next.month() != date.month())
+}
+
+fn is_last_day_of_february(date: NaiveDate) -> bool {
+    date.month() == 2 && is_last_day_of_month(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::date;
+    use chrono::Duration;
+
+    fn period(start: NaiveDate, end: NaiveDate, frequency: u32) -> AccrualPeriod {
+        AccrualPeriod {
+            start,
+            end,
+            frequency,
+            maturity: end,
+        }
+    }
+
+    fn assert_fraction(convention: DayCountConvention, from: NaiveDate, to: NaiveDate, period: &AccrualPeriod, expected: Decimal) {
+        let fraction = convention.year_fraction(from, to, period);
+        assert!(
+            (fraction - expected).abs() < dec!(0.000000000001),
+            "{} from {} to {} is {}, expected {}",
+            convention,
+            from,
+            to,
+            fraction,
+            expected
+        );
+    }
+
+    #[test]
+    fn actual_day_conventions() {
+        let (from, to) = (date(2003, 11, 1), date(2004, 5, 1));
+        let semi_annual = period(from, to, 2);
+        assert_fraction(DayCountConvention::Act365Fixed, from, to, &semi_annual, dec!(182) / dec!(365));
+        assert_fraction(DayCountConvention::Act360, from, to, &semi_annual, dec!(182) / dec!(360));
+        // the period ends in a leap year
+        assert_fraction(DayCountConvention::Act365L, from, to, &semi_annual, dec!(182) / dec!(366));
+        // annual periods only use 366 when they contain 29 February
+        let (from, to) = (date(1999, 7, 1), date(2000, 7, 1));
+        assert_fraction(DayCountConvention::Act365L, from, to, &period(from, to, 1), Decimal::ONE);
+        let (from, to) = (date(2000, 3, 1), date(2001, 3, 1));
+        assert_fraction(DayCountConvention::Act365L, from, to, &period(from, to, 1), Decimal::ONE);
+    }
+
+    #[test]
+    fn act_act_isda_reference_dates() {
+        let cases = [
+            (date(2003, 11, 1), date(2004, 5, 1), dec!(61) / dec!(365) + dec!(121) / dec!(366)),
+            (date(1999, 2, 1), date(1999, 7, 1), dec!(150) / dec!(365)),
+            (date(1999, 7, 1), date(2000, 7, 1), dec!(184) / dec!(365) + dec!(182) / dec!(366)),
+            (date(2002, 8, 15), date(2003, 7, 15), dec!(334) / dec!(365)),
+            (date(2003, 7, 15), date(2004, 1, 15), dec!(170) / dec!(365) + dec!(14) / dec!(366)),
+            (date(1999, 7, 30), date(2000, 1, 30), dec!(155) / dec!(365) + dec!(29) / dec!(366)),
+            (date(2000, 1, 30), date(2000, 6, 30), dec!(152) / dec!(366)),
+            (date(1999, 11, 30), date(2000, 4, 30), dec!(32) / dec!(365) + dec!(120) / dec!(366)),
+        ];
+        for (from, to, expected) in cases {
+            assert_fraction(DayCountConvention::ActActIsda, from, to, &period(from, to, 1), expected);
+        }
+    }
+
+    #[test]
+    fn act_act_icma_reference_dates() {
+        let cases = [
+            // regular semi-annual period
+            (date(2003, 11, 1), date(2004, 5, 1), 2, dec!(0.5)),
+            // short first periods
+            (date(1999, 2, 1), date(1999, 7, 1), 1, dec!(150) / dec!(365)),
+            (date(2002, 8, 15), date(2003, 7, 15), 1, dec!(334) / dec!(365)),
+            // regular annual period
+            (date(1999, 7, 1), date(2000, 7, 1), 1, Decimal::ONE),
+            // long first quarterly period
+            (date(1999, 11, 30), date(2000, 4, 30), 4, dec!(91) / dec!(364) + dec!(61) / dec!(368)),
+        ];
+        for (from, to, frequency, expected) in cases {
+            assert_fraction(DayCountConvention::ActActIcma, from, to, &period(from, to, frequency), expected);
+        }
+    }
+
+    #[test]
+    fn thirty_360_reference_dates() {
+        // ISDA 2006 section 4.16 examples, in days for 30/360 US, 30E/360 and 30E/360 ISDA
+        let cases = [
+            (date(2007, 1, 15), date(2007, 1, 30), 15, 15, 15),
+            (date(2007, 1, 15), date(2007, 2, 15), 30, 30, 30),
+            (date(2007, 1, 15), date(2007, 7, 15), 180, 180, 180),
+            (date(2007, 9, 30), date(2008, 3, 31), 180, 180, 180),
+            (date(2007, 9, 30), date(2007, 10, 31), 30, 30, 30),
+            (date(2007, 9, 30), date(2008, 9, 30), 360, 360, 360),
+            (date(2007, 1, 15), date(2007, 1, 31), 16, 15, 15),
+            (date(2007, 1, 31), date(2007, 2, 28), 28, 28, 30),
+            (date(2007, 2, 28), date(2007, 3, 31), 30, 32, 30),
+            (date(2006, 8, 31), date(2007, 2, 28), 178, 178, 180),
+            (date(2007, 2, 28), date(2007, 8, 31), 180, 182, 180),
+            (date(2007, 2, 14), date(2007, 2, 28), 14, 14, 16),
+            (date(2007, 2, 26), date(2008, 2, 29), 363, 363, 364),
+            (date(2008, 2, 29), date(2009, 2, 28), 360, 359, 360),
+            (date(2008, 2, 29), date(2008, 3, 30), 30, 31, 30),
+            (date(2008, 2, 29), date(2008, 3, 31), 30, 31, 30),
+            (date(2007, 2, 28), date(2007, 3, 5), 5, 7, 5),
+            (date(2007, 10, 31), date(2007, 11, 28), 28, 28, 28),
+            (date(2007, 8, 31), date(2008, 2, 29), 179, 179, 180),
+            (date(2008, 2, 29), date(2008, 8, 31), 180, 181, 180),
+            (date(2008, 8, 31), date(2009, 2, 28), 178, 178, 180),
+            (date(2009, 2, 28), date(2009, 8, 31), 180, 182, 180),
+        ];
+        // none of the end dates is the maturity, so 30E/360 ISDA treats February month ends as the 30th
+        let period = period(date(2006, 1, 1), date(2010, 1, 1), 1);
+        for (from, to, us, eurobond, isda) in cases {
+            assert_fraction(DayCountConvention::Thirty360Us, from, to, &period, Decimal::from(us) / dec!(360));
+            assert_fraction(DayCountConvention::ThirtyE360, from, to, &period, Decimal::from(eurobond) / dec!(360));
+            assert_fraction(DayCountConvention::ThirtyE360Isda, from, to, &period, Decimal::from(isda) / dec!(360));
+        }
+        let maturity = date(2007, 2, 28);
+        assert_fraction(
+            DayCountConvention::ThirtyE360Isda,
+            date(2007, 1, 31),
+            maturity,
+            &AccrualPeriod { maturity, ..period },
+            dec!(28) / dec!(360),
+        );
+    }
+
+    #[test]
+    fn daily_accrual_fractions_add_up_to_the_period() {
+        let (start, end) = (date(2023, 1, 1), date(2024, 3, 31));
+        let period = period(start, end, 1);
+        for convention in DayCountConvention::ALL {
+            let days: Decimal = start
+                .iter_days()
+                .take_while(|day| *day < end)
+                .map(|day| convention.accrual_fraction(day, day + Duration::days(1), &period))
+                .sum();
+            assert_eq!(days.round_dp(20), convention.year_fraction(start, end, &period).round_dp(20), "{}", convention);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::{anyhow, Error};
+use chrono::NaiveDate;
+use interest_app::history::LoanVersion;
+use interest_app::import::ImportReport;
+use interest_app::payoff::PayoffQuote;
+use interest_app::portfolio::PortfolioSummary;
+use interest_app::query::{self, QueryResult};
+use interest_app::report::{self, ReportOptions};
+use interest_app::schedule::SchedulePeriod;
+use interest_app::{AccruedInterest, Loan, RoundingPolicy};
+use std::io::{self, Write};
+
+/// Prints the summary and accrual table. With a page size and no page the table is paged
+/// interactively, pressing enter shows the next page and q stops.
+pub fn print_report(loan: &Loan, options: &ReportOptions) -> Result<(), Error> {
+    println!("Loan Interest Calculation Results");
+    println!("--------------------------------");
+    for line in report::summary(loan) {
+        println!("{}", line);
+    }
+    println!();
+
+    let table = report::accrual_table(loan, options);
+    let (heading, rows) = table.split_first().expect("the table always has a heading");
+    let page_size = options.page_size.unwrap_or(rows.len()).max(1);
+    let pages: Vec<&[String]> = rows.chunks(page_size).collect();
+    let page_count = pages.len().max(1);
+
+    if let Some(page) = options.page {
+        let rows = pages
+            .get(page.wrapping_sub(1))
+            .ok_or(anyhow!("Page {} does not exist, the report has {} pages.", page, page_count))?;
+        println!("{}", heading);
+        rows.iter().for_each(|row| println!("{}", row));
+        println!("Page {} of {}\n", page, page_count);
+        return Ok(());
+    }
+
+    println!("{}", heading);
+    for (index, rows) in pages.iter().enumerate() {
+        rows.iter().for_each(|row| println!("{}", row));
+        if index + 1 < page_count {
+            print!("-- Page {} of {}, press enter for more or q to stop -- ", index + 1, page_count);
+            io::stdout().flush().unwrap();
+            let mut answer = String::new();
+            io::stdin().read_line(&mut answer).unwrap();
+            if answer.trim().eq_ignore_ascii_case("q") {
+                break;
+            }
+            println!("{}", heading);
+        }
+    }
+    println!();
+    Ok(())
+}
+
+/// Prints the interest accrued over `from` to `to`, from being the loan's start date for accrued-to-date figures.
+pub fn print_accrued(loan: &Loan, from: NaiveDate, to: NaiveDate, accrued: &AccruedInterest) {
+    let policy = loan.rounding_policy;
+    let currency = loan.loan_currency;
+    println!("Accrued from {} to {} ({} days)", from, to, accrued.days);
+    println!("Interest:        {}", report::format_amount(accrued.interest, currency, policy));
+    println!("  Base:          {}", report::format_amount(accrued.interest_no_margin, currency, policy));
+    println!("  Margin:        {}", report::format_amount(accrued.interest - accrued.interest_no_margin, currency, policy));
+    if loan.facility.is_some() {
+        println!("Commitment Fee:  {}", report::format_amount(accrued.commitment_fee, currency, policy));
+    }
+    if !loan.overdue_events.is_empty() {
+        println!("Default Int.:    {}", report::format_amount(accrued.default_interest, currency, policy));
+    }
+    println!();
+}
+
+pub fn print_amortization_schedule(loan: &Loan, periods: &[SchedulePeriod]) {
+    let policy = loan.rounding_policy;
+    let dp = loan.loan_currency.minor_units;
+    println!("Amortization Schedule ({})", loan.loan_currency);
+    let mut rows = vec![["Payment", "Start", "End", "Opening Balance", "Interest", "Principal", "Total Payment", "Closing Balance"]
+        .map(String::from)
+        .to_vec()];
+    for period in periods {
+        rows.push(vec![
+            period.payment_date.to_string(),
+            period.start_date.to_string(),
+            period.end_date.to_string(),
+            report::group_thousands(period.opening_balance, dp, policy),
+            report::group_thousands(period.interest_due, dp, policy),
+            report::group_thousands(period.principal_due, dp, policy),
+            report::group_thousands(period.total_payment, dp, policy),
+            report::group_thousands(period.closing_balance, dp, policy),
+        ]);
+    }
+    for line in report::align(&rows) {
+        println!("{}", line);
+    }
+    println!();
+}
+
+pub fn print_history(loan_id: u32, history: &[LoanVersion]) {
+    println!("History of Loan ID {}", loan_id);
+    if history.is_empty() {
+        println!("No amendments have been recorded.\n");
+        return;
+    }
+    for version in history {
+        println!(
+            "Version {}: {} by {} at {}",
+            version.version,
+            version.amendment,
+            version.changed_by,
+            version.changed_at.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+        for change in version.changes.iter() {
+            println!("    {}: {} -> {}", change.field, change.old_value, change.new_value);
+        }
+    }
+    println!();
+}
+
+pub fn print_import_report(report: &ImportReport) {
+    println!("Imported {} loans, rejected {} rows.", report.imported.len(), report.rejected.len());
+    for (line_number, loan_id) in report.imported.iter() {
+        println!("Line {}: added with ID {}", line_number, loan_id);
+    }
+    for (line_number, error) in report.rejected.iter() {
+        println!("Line {}: {}", line_number, error);
+    }
+    println!();
+}
+
+pub fn print_portfolio(summary: &PortfolioSummary) {
+    // loans can each have their own rounding policy, portfolio figures use bankers rounding
+    let policy = RoundingPolicy::Bankers;
+    let reporting_currency = summary.reporting_currency;
+    println!("Portfolio Totals in {} as of {}", reporting_currency, summary.date);
+
+    let mut rows = vec![vec![
+        "Currency".to_string(),
+        "Loans".to_string(),
+        "Principal".to_string(),
+        "Interest".to_string(),
+        "FX Rate".to_string(),
+        format!("Principal ({})", reporting_currency),
+        format!("Interest ({})", reporting_currency),
+    ]];
+    for (currency, totals, rate) in summary.by_currency.values() {
+        rows.push(vec![
+            currency.to_string(),
+            totals.loans.to_string(),
+            report::group_thousands(totals.principal, currency.minor_units, policy),
+            report::group_thousands(totals.interest, currency.minor_units, policy),
+            rate.round_dp(6).normalize().to_string(),
+            report::group_thousands(totals.principal * rate, reporting_currency.minor_units, policy),
+            report::group_thousands(totals.interest * rate, reporting_currency.minor_units, policy),
+        ]);
+    }
+    rows.push(vec![
+        "Total".to_string(),
+        summary.converted.loans.to_string(),
+        String::new(),
+        String::new(),
+        String::new(),
+        report::group_thousands(summary.converted.principal, reporting_currency.minor_units, policy),
+        report::group_thousands(summary.converted.interest, reporting_currency.minor_units, policy),
+    ]);
+    for line in report::align(&rows) {
+        println!("{}", line);
+    }
+    println!();
+}
+
+pub fn print_query_results(results: &[QueryResult]) {
+    println!("{} loans found", results.len());
+    if results.is_empty() {
+        println!();
+        return;
+    }
+    let mut rows = vec![["ID", "Status", "Amount", "Start", "End", "All-in Rate", "Total Interest", "Remaining Years"]
+        .map(String::from)
+        .to_vec()];
+    for result in results {
+        let loan = result.loan;
+        rows.push(vec![
+            result.loan_id.to_string(),
+            result.status.to_string(),
+            report::format_amount(loan.loan_amount, loan.loan_currency, loan.rounding_policy),
+            loan.start_date.to_string(),
+            loan.end_date.to_string(),
+            report::format_rate(loan.all_in_rate()),
+            report::format_amount(loan.total_interest, loan.loan_currency, loan.rounding_policy),
+            format!("{:.2}", result.remaining_life),
+        ]);
+    }
+    for line in report::align(&rows) {
+        println!("{}", line);
+    }
+    println!();
+
+    let mut rows = vec![["Currency", "Loans", "Total Principal", "Weighted Avg Rate", "Weighted Avg Remaining Years"]
+        .map(String::from)
+        .to_vec()];
+    for (currency, statistics) in query::statistics(results).values() {
+        rows.push(vec![
+            currency.to_string(),
+            statistics.count.to_string(),
+            report::format_amount(statistics.total_principal, *currency, RoundingPolicy::Bankers),
+            report::format_rate(statistics.weighted_average_rate),
+            format!("{:.2}", statistics.weighted_average_remaining_life),
+        ]);
+    }
+    for line in report::align(&rows) {
+        println!("{}", line);
+    }
+    println!();
+}
+
+pub fn print_payoff_quote(loan: &Loan, quote: &PayoffQuote) {
+    let policy = loan.rounding_policy;
+    let currency = loan.loan_currency;
+    println!("Payoff Quote for {}", quote.payoff_date);
+    let mut rows = vec![
+        vec!["Outstanding Principal".to_string(), report::format_amount(quote.outstanding_principal, currency, policy)],
+        vec![
+            format!("Accrued Interest from {}", quote.accrued_from),
+            report::format_amount(quote.accrued.interest, currency, policy),
+        ],
+    ];
+    if loan.facility.is_some() {
+        rows.push(vec!["Accrued Commitment Fee".to_string(), report::format_amount(quote.accrued.commitment_fee, currency, policy)]);
+    }
+    if !loan.overdue_events.is_empty() {
+        rows.push(vec!["Overdue Payments".to_string(), report::format_amount(quote.overdue_amount, currency, policy)]);
+        rows.push(vec!["Accrued Default Interest".to_string(), report::format_amount(quote.accrued.default_interest, currency, policy)]);
+    }
+    rows.push(vec!["Break Cost".to_string(), report::format_amount(quote.break_cost, currency, policy)]);
+    rows.push(vec!["Total".to_string(), report::format_amount(quote.total, currency, policy)]);
+    rows.push(vec!["Per Diem".to_string(), report::format_amount(quote.per_diem, currency, policy)]);
+    for line in report::align(&rows) {
+        println!("{}", line);
+    }
+    println!();
+}
@@ -0,0 +1,133 @@
+use anyhow::{anyhow, Context, Error};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::currency::Currency;
+use crate::Loan;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// A loan's terms and totals with its daily accruals, as written to JSON exports.
+#[derive(Debug, Serialize)]
+struct LoanExport {
+    loan_id: u32,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    loan_amount: Decimal,
+    loan_currency: Currency,
+    base_interest_rate: Decimal,
+    margin: Decimal,
+    day_count_convention: String,
+    compounding: String,
+    total_interest: Decimal,
+    total_commitment_fee: Decimal,
+    total_default_interest: Decimal,
+    daily_accruals: Vec<DailyAccrual>,
+}
+
+#[derive(Debug, Serialize)]
+struct DailyAccrual {
+    date: NaiveDate,
+    days_elapsed: i64,
+    day_interest: Decimal,
+    day_interest_no_margin: Decimal,
+    default_interest: Decimal,
+    cumulative_interest: Decimal,
+}
+
+/// Returns the loan's daily accruals with the running total of interest, in date order.
+fn daily_accruals(loan: &Loan) -> Vec<DailyAccrual> {
+    let mut cumulative_interest = Decimal::ZERO;
+    loan.daily_information
+        .iter()
+        .map(|(date, daily)| {
+            cumulative_interest += daily.day_interest;
+            DailyAccrual {
+                date: *date,
+                days_elapsed: daily.days_elapsed,
+                day_interest: daily.day_interest,
+                day_interest_no_margin: daily.day_interest_no_margin,
+                default_interest: daily.default_interest,
+                cumulative_interest,
+            }
+        })
+        .collect()
+}
+
+/// Writes the daily accruals of `loans` to `path`. Amounts are written at full precision so
+/// reporting tools can apply their own rounding.
+pub fn export_loans<'a>(loans: impl Iterator<Item = (u32, &'a Loan)>, format: ExportFormat, path: &Path) -> Result<(), Error> {
+    let contents = match format {
+        ExportFormat::Csv => {
+            let mut csv = String::from("loan_id,date,days_elapsed,day_interest,day_interest_no_margin,default_interest,cumulative_interest\n");
+            for (loan_id, loan) in loans {
+                for accrual in daily_accruals(loan) {
+                    writeln!(
+                        csv,
+                        "{},{},{},{},{},{},{}",
+                        loan_id,
+                        accrual.date,
+                        accrual.days_elapsed,
+                        accrual.day_interest,
+                        accrual.day_interest_no_margin,
+                        accrual.default_interest,
+                        accrual.cumulative_interest
+                    )?;
+                }
+            }
+            csv
+        }
+        ExportFormat::Json => {
+            let exports: Vec<LoanExport> = loans
+                .map(|(loan_id, loan)| LoanExport {
+                    loan_id,
+                    start_date: loan.start_date,
+                    end_date: loan.end_date,
+                    loan_amount: loan.loan_amount,
+                    loan_currency: loan.loan_currency,
+                    base_interest_rate: loan.base_interest_rate,
+                    margin: loan.margin,
+                    day_count_convention: loan.day_count_convention.to_string(),
+                    compounding: loan.compounding.to_string(),
+                    total_interest: loan.total_interest,
+                    total_commitment_fee: loan.total_commitment_fee,
+                    total_default_interest: loan.total_default_interest,
+                    daily_accruals: daily_accruals(loan),
+                })
+                .collect();
+            serde_json::to_string_pretty(&exports)?
+        }
+    };
+    fs::write(path, contents).with_context(|| format!("Could not write {}", path.display()))
+}
+
+impl ExportFormat {
+    /// Guesses the format from the file extension, used when no format is given.
+    pub fn from_path(path: &Path) -> Option<ExportFormat> {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| extension.parse().ok())
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            other => Err(anyhow!("Unknown export format '{}'. Expected csv or json.", other)),
+        }
+    }
+}
+
@@ -0,0 +1,152 @@
+use anyhow::{anyhow, Error};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LedgerEventType {
+    Drawdown,
+    Repayment,
+}
+
+/// A movement of drawn principal on a facility.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerEvent {
+    pub date: NaiveDate,
+    pub event_type: LedgerEventType,
+    pub amount: Decimal,
+}
+
+impl LedgerEvent {
+    /// The change to the drawn balance, drawdowns increase it and repayments reduce it.
+    pub fn signed_amount(&self) -> Decimal {
+        match self.event_type {
+            LedgerEventType::Drawdown => self.amount,
+            LedgerEventType::Repayment => -self.amount,
+        }
+    }
+}
+
+/// Limit and fee terms of a revolving credit facility.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Facility {
+    /// Maximum amount that can be drawn at any one time.
+    pub limit: Decimal,
+    /// Annual fee rate charged on the undrawn part of the limit.
+    pub commitment_fee_rate: Decimal,
+}
+
+impl Facility {
+    /// Commitment fee for one day given the drawn balance and the day's year fraction.
+    pub fn commitment_fee(&self, drawn: Decimal, day_fraction: Decimal) -> Decimal {
+        (self.limit - drawn).max(Decimal::ZERO) * self.commitment_fee_rate * day_fraction
+    }
+}
+
+/// Nets the ledger into the change of drawn balance on each date.
+pub fn net_movements(ledger: &[LedgerEvent]) -> BTreeMap<NaiveDate, Decimal> {
+    let mut movements = BTreeMap::new();
+    for event in ledger {
+        *movements.entry(event.date).or_insert(Decimal::ZERO) += event.signed_amount();
+    }
+    movements
+}
+
+/// Parses comma separated `YYYY-MM-DD=+amount` (drawdown) or `YYYY-MM-DD=-amount` (repayment) entries.
+pub fn parse_ledger(input: &str) -> Result<Vec<LedgerEvent>, Error> {
+    let mut ledger = input
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<LedgerEvent>, Error>>()?;
+    ledger.sort_by_key(|event| event.date);
+    Ok(ledger)
+}
+
+impl FromStr for LedgerEvent {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (date, amount) = s
+            .trim()
+            .split_once('=')
+            .ok_or(anyhow!("Invalid ledger event '{}'. Expected YYYY-MM-DD=+amount or YYYY-MM-DD=-amount.", s.trim()))?;
+        let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")?;
+        let amount = amount.trim().parse::<Decimal>()?;
+        let event_type = if amount.is_sign_negative() {
+            LedgerEventType::Repayment
+        } else {
+            LedgerEventType::Drawdown
+        };
+        Ok(LedgerEvent {
+            date,
+            event_type,
+            amount: amount.abs(),
+        })
+    }
+}
+
+impl fmt::Display for LedgerEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = match self.event_type {
+            LedgerEventType::Drawdown => "+",
+            LedgerEventType::Repayment => "-",
+        };
+        write!(f, "{}={}{}", self.date, sign, self.amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{calculated, date, loan};
+    use crate::MarketData;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn ledger_entries_are_parsed_in_date_order() {
+        let ledger = parse_ledger("2024-01-21=-200000, 2024-01-11=+600000").unwrap();
+        assert_eq!(ledger[0].event_type, LedgerEventType::Drawdown);
+        assert_eq!(ledger[1].signed_amount(), dec!(-200000));
+        assert_eq!(ledger[1].to_string(), "2024-01-21=-200000");
+        assert!(parse_ledger("2024-01-11").is_err());
+    }
+
+    #[test]
+    fn commitment_fee_is_charged_on_the_undrawn_limit() {
+        // undrawn for 10 days, 600,000 drawn for 10 days then 400,000 for the last 10 days
+        let mut loan = loan(date(2024, 1, 1), date(2024, 1, 31));
+        loan.loan_amount = Decimal::ZERO;
+        loan.facility = Some(Facility {
+            limit: dec!(1000000),
+            commitment_fee_rate: dec!(0.005),
+        });
+        loan.ledger = parse_ledger("2024-01-11=+600000,2024-01-21=-200000").unwrap();
+        let loan = calculated(loan);
+
+        // 0.5% ACT/360 on 1,000,000, 400,000 and 600,000 undrawn for 10 days each
+        let fee = dec!(0.005) * dec!(10) * dec!(2000000) / dec!(360);
+        assert_eq!(loan.total_commitment_fee.round_dp(10), fee.round_dp(10));
+        assert_eq!(
+            loan.accrued_to(date(2024, 1, 11)).commitment_fee.round_dp(10),
+            (dec!(0.005) * dec!(10) * dec!(1000000) / dec!(360)).round_dp(10)
+        );
+        // interest is only charged on the drawn balance
+        assert_eq!(loan.total_interest.round_dp(10), (dec!(0.06) * dec!(10) * dec!(1000000) / dec!(360)).round_dp(10));
+    }
+
+    #[test]
+    fn drawing_more_than_the_limit_is_an_error() {
+        let mut loan = loan(date(2024, 1, 1), date(2024, 1, 31));
+        loan.facility = Some(Facility {
+            limit: dec!(1000000),
+            commitment_fee_rate: dec!(0.005),
+        });
+        loan.ledger = parse_ledger("2024-01-11=+1").unwrap();
+        assert!(loan.calculate_interest(&MarketData::default()).is_err());
+    }
+}
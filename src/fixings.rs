@@ -0,0 +1,149 @@
+use anyhow::{anyhow, Context, Error};
+use chrono::{Duration, NaiveDate};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::calendar::HolidayCalendar;
+
+/// What to do when a benchmark has no fixing published for the date being accrued.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FixingFallback {
+    /// Use the most recent fixing before the date, i.e. the previous business day's fixing.
+    PreviousBusinessDay,
+    /// Fail the calculation.
+    Error,
+    /// Use a fixed rate agreed in the loan documentation.
+    FixedRate(Decimal),
+}
+
+/// Historical benchmark fixings (e.g. SOFR, EURIBOR 3M, SONIA) keyed by index name and date.
+#[derive(Clone, Debug, Default)]
+pub struct RateFixings {
+    // index names are stored upper case so lookups are case insensitive
+    fixings: BTreeMap<String, BTreeMap<NaiveDate, Decimal>>,
+}
+
+impl RateFixings {
+    pub fn insert(&mut self, index: &str, date: NaiveDate, rate: Decimal) {
+        self.fixings
+            .entry(index.trim().to_uppercase())
+            .or_default()
+            .insert(date, rate);
+    }
+
+    /// Loads fixings from a CSV file with the header `index,date,rate` where rate is in %.
+    /// Returns the number of fixings loaded, existing fixings for the same index and date are replaced.
+    pub fn load_csv(&mut self, path: &Path) -> Result<usize, Error> {
+        let contents = fs::read_to_string(path).with_context(|| format!("Could not read {}", path.display()))?;
+        let mut loaded = 0;
+        for (line_number, line) in contents.lines().enumerate().skip(1) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let columns: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [index, date, rate] = columns[..] else {
+                return Err(anyhow!("Line {}: expected 3 columns (index,date,rate) but found {}.", line_number + 1, columns.len()));
+            };
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").with_context(|| format!("Line {}: invalid date", line_number + 1))?;
+            // divide by 100 to convert to %
+            let rate = rate.parse::<Decimal>().with_context(|| format!("Line {}: invalid rate", line_number + 1))? / dec!(100);
+            self.insert(index, date, rate);
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// Returns the fixing of `index` for `date`. Weekends and holidays of `calendar` take the previous business
+    /// day's fixing, `fallback` is applied when a business day has no fixing published.
+    pub fn fixing(&self, index: &str, date: NaiveDate, fallback: FixingFallback, calendar: &HolidayCalendar) -> Result<Decimal, Error> {
+        let index_fixings = self
+            .fixings
+            .get(&index.trim().to_uppercase())
+            .ok_or(anyhow!("No fixings loaded for index {}.", index))?;
+        let mut fixing_date = date;
+        while !calendar.is_business_day(fixing_date) {
+            fixing_date -= Duration::days(1);
+        }
+        if let Some(rate) = index_fixings.get(&fixing_date) {
+            return Ok(*rate);
+        }
+        match fallback {
+            FixingFallback::PreviousBusinessDay => index_fixings
+                .range(..fixing_date)
+                .next_back()
+                .map(|(_, rate)| *rate)
+                .ok_or(anyhow!("No {} fixing on or before {}.", index, fixing_date)),
+            FixingFallback::Error => Err(anyhow!("No {} fixing for {}.", index, fixing_date)),
+            FixingFallback::FixedRate(rate) => Ok(rate),
+        }
+    }
+
+    pub fn indices(&self) -> impl Iterator<Item = (&String, usize)> {
+        self.fixings.iter().map(|(index, fixings)| (index, fixings.len()))
+    }
+}
+
+impl FromStr for FixingFallback {
+    type Err = Error;
+
+    /// Parses `previous`, `error` or `fixed=<rate%>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalised = s.trim().to_lowercase();
+        match normalised.as_str() {
+            "previous" => Ok(FixingFallback::PreviousBusinessDay),
+            "error" => Ok(FixingFallback::Error),
+            _ => match normalised.split_once('=') {
+                // divide by 100 to convert to %
+                Some(("fixed", rate)) => Ok(FixingFallback::FixedRate(rate.trim().parse::<Decimal>()? / dec!(100))),
+                _ => Err(anyhow!("Unknown fixing fallback '{}'. Expected previous, error or fixed=<rate>.", s.trim())),
+            },
+        }
+    }
+}
+
+impl fmt::Display for FixingFallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixingFallback::PreviousBusinessDay => write!(f, "previous"),
+            FixingFallback::Error => write!(f, "error"),
+            FixingFallback::FixedRate(rate) => write!(f, "fixed={}", (rate * dec!(100)).normalize()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::date;
+
+    fn sofr() -> RateFixings {
+        let mut fixings = RateFixings::default();
+        fixings.insert("SOFR", date(2024, 1, 5), dec!(0.053));
+        fixings.insert("SOFR", date(2024, 1, 8), dec!(0.0531));
+        fixings
+    }
+
+    #[test]
+    fn weekends_take_the_previous_business_day_fixing() {
+        let calendar = HolidayCalendar::default();
+        for fallback in [FixingFallback::Error, FixingFallback::FixedRate(dec!(0.01))] {
+            assert_eq!(sofr().fixing("SOFR", date(2024, 1, 6), fallback, &calendar).unwrap(), dec!(0.053));
+            assert_eq!(sofr().fixing("SOFR", date(2024, 1, 7), fallback, &calendar).unwrap(), dec!(0.053));
+        }
+    }
+
+    #[test]
+    fn fallback_applies_to_missing_business_day_fixings() {
+        let calendar = HolidayCalendar::default();
+        let missing = date(2024, 1, 9);
+        assert_eq!(sofr().fixing("SOFR", missing, FixingFallback::PreviousBusinessDay, &calendar).unwrap(), dec!(0.0531));
+        assert_eq!(sofr().fixing("SOFR", missing, FixingFallback::FixedRate(dec!(0.01)), &calendar).unwrap(), dec!(0.01));
+        assert!(sofr().fixing("SOFR", missing, FixingFallback::Error, &calendar).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use anyhow::{anyhow, Context, Error};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::currency::Currency;
+
+/// Historical FX rates keyed by currency pair and date, where one unit of the base currency buys `rate` of the quote currency.
+#[derive(Clone, Debug, Default)]
+pub struct FxRates {
+    rates: BTreeMap<(&'static str, &'static str), BTreeMap<NaiveDate, Decimal>>,
+}
+
+impl FxRates {
+    pub fn insert(&mut self, base: Currency, quote: Currency, date: NaiveDate, rate: Decimal) {
+        self.rates.entry((base.code, quote.code)).or_default().insert(date, rate);
+    }
+
+    /// Loads rates from a CSV file with the header `date,base,quote,rate`, e.g. `2024-01-31,EUR,USD,1.0837`.
+    /// Returns the number of rates loaded, existing rates for the same pair and date are replaced.
+    pub fn load_csv(&mut self, path: &Path) -> Result<usize, Error> {
+        let contents = fs::read_to_string(path).with_context(|| format!("Could not read {}", path.display()))?;
+        let mut loaded = 0;
+        for (line_number, line) in contents.lines().enumerate().skip(1) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let columns: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [date, base, quote, rate] = columns[..] else {
+                return Err(anyhow!("Line {}: expected 4 columns (date,base,quote,rate) but found {}.", line_number + 1, columns.len()));
+            };
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").with_context(|| format!("Line {}: invalid date", line_number + 1))?;
+            let base = base.parse::<Currency>().with_context(|| format!("Line {}: invalid base currency", line_number + 1))?;
+            let quote = quote.parse::<Currency>().with_context(|| format!("Line {}: invalid quote currency", line_number + 1))?;
+            let rate = rate.parse::<Decimal>().with_context(|| format!("Line {}: invalid rate", line_number + 1))?;
+            if rate <= Decimal::ZERO {
+                return Err(anyhow!("Line {}: the rate must be positive.", line_number + 1));
+            }
+            self.insert(base, quote, date, rate);
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// Number of rates loaded for each currency pair, e.g. `EUR/USD`.
+    pub fn pairs(&self) -> impl Iterator<Item = (String, usize)> + '_ {
+        self.rates.iter().map(|((base, quote), rates)| (format!("{}/{}", base, quote), rates.len()))
+    }
+
+    /// Most recent rate on or before `date` quoted either way round.
+    fn pair_rate(&self, from: Currency, to: Currency, date: NaiveDate) -> Option<Decimal> {
+        let latest = |base: Currency, quote: Currency| {
+            self.rates
+                .get(&(base.code, quote.code))
+                .and_then(|rates| rates.range(..=date).next_back())
+                .map(|(_, rate)| *rate)
+        };
+        latest(from, to).or_else(|| latest(to, from).map(|rate| Decimal::ONE / rate))
+    }
+
+    /// Rate converting an amount in `from` into `to` on `date`, using the most recent rate on or before the date.
+    /// Pairs without a rate of their own are crossed through USD.
+    pub fn rate(&self, from: Currency, to: Currency, date: NaiveDate) -> Result<Decimal, Error> {
+        if from == to {
+            return Ok(Decimal::ONE);
+        }
+        self.pair_rate(from, to, date)
+            .or_else(|| Some(self.pair_rate(from, Currency::USD, date)? * self.pair_rate(Currency::USD, to, date)?))
+            .ok_or(anyhow!("No {}/{} FX rate on or before {}.", from, to, date))
+    }
+}
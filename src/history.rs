@@ -0,0 +1,198 @@
+use anyhow::Error;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::Loan;
+
+/// Environment variable used to record who made an amendment.
+pub const USER_VARIABLE: &str = "INTEREST_APP_USER";
+
+/// Fields worked out by `calculate_interest` rather than entered, so they are left out of diffs.
+const CALCULATED_FIELDS: [&str; 5] = [
+    "daily_information",
+    "principal_movements",
+    "total_interest",
+    "total_commitment_fee",
+    "total_default_interest",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Amendment {
+    Created,
+    Updated,
+    Archived,
+    Restored,
+    Deleted,
+}
+
+/// A field whose value differs between two versions of a loan, values are shown as text.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old_value: String,
+    pub new_value: String,
+}
+
+/// One entry of a loan's append-only history, holding the loan's terms as they were after the amendment.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LoanVersion {
+    pub version: u32,
+    pub amendment: Amendment,
+    pub changed_at: DateTime<Utc>,
+    pub changed_by: String,
+    pub changes: Vec<FieldChange>,
+    // the daily information is dropped to keep the book small, it is recalculated when needed
+    pub loan: Loan,
+}
+
+/// The login name, recorded against amendments when no user is given.
+pub fn login_name() -> String {
+    ["USER", "USERNAME"]
+        .iter()
+        .find_map(|variable| std::env::var(variable).ok().filter(|user| !user.trim().is_empty()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Appends a version of `loan` to `history`, with the fields changed since `previous`.
+pub fn record(
+    history: &mut Vec<LoanVersion>,
+    amendment: Amendment,
+    changed_by: &str,
+    previous: Option<&Loan>,
+    loan: &Loan,
+) -> Result<(), Error> {
+    let changes = match previous {
+        Some(previous) => diff(previous, loan)?,
+        None => Vec::new(),
+    };
+    let mut snapshot = loan.clone();
+    snapshot.daily_information.clear();
+    history.push(LoanVersion {
+        version: history.last().map_or(1, |version| version.version + 1),
+        amendment,
+        changed_at: Utc::now(),
+        changed_by: changed_by.to_string(),
+        changes,
+        loan: snapshot,
+    });
+    Ok(())
+}
+
+/// Compares the entered terms of two loans field by field. Nested values such as the facility and
+/// dated schedules are compared per entry, e.g. `facility.limit` or `base_rate_schedule.2024-01-01`.
+pub fn diff(old: &Loan, new: &Loan) -> Result<Vec<FieldChange>, Error> {
+    let mut old_fields = BTreeMap::new();
+    flatten("", serde_json::to_value(old)?, &mut old_fields);
+    let mut new_fields = BTreeMap::new();
+    flatten("", serde_json::to_value(new)?, &mut new_fields);
+
+    let mut fields: Vec<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+    fields.sort();
+    fields.dedup();
+    Ok(fields
+        .into_iter()
+        .filter(|field| !CALCULATED_FIELDS.iter().any(|calculated| field.split('.').next() == Some(*calculated)))
+        .filter_map(|field| {
+            let old_value = old_fields.get(field).cloned().unwrap_or_else(|| "none".to_string());
+            let new_value = new_fields.get(field).cloned().unwrap_or_else(|| "none".to_string());
+            (old_value != new_value).then(|| FieldChange {
+                field: field.clone(),
+                old_value,
+                new_value,
+            })
+        })
+        .collect())
+}
+
+fn flatten(path: &str, value: Value, fields: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let path = if path.is_empty() { key } else { format!("{}.{}", path, key) };
+                flatten(&path, value, fields);
+            }
+        }
+        Value::String(text) => {
+            fields.insert(path.to_string(), text);
+        }
+        Value::Null => {
+            fields.insert(path.to_string(), "none".to_string());
+        }
+        other => {
+            fields.insert(path.to_string(), other.to_string());
+        }
+    }
+}
+
+impl fmt::Display for Amendment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Amendment::Created => "created",
+            Amendment::Updated => "updated",
+            Amendment::Archived => "archived",
+            Amendment::Restored => "restored",
+            Amendment::Deleted => "deleted",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::facility::Facility;
+    use crate::test_support::{calculated, date, loan};
+    use rust_decimal_macros::dec;
+
+    fn change(field: &str, old_value: &str, new_value: &str) -> FieldChange {
+        FieldChange {
+            field: field.to_string(),
+            old_value: old_value.to_string(),
+            new_value: new_value.to_string(),
+        }
+    }
+
+    #[test]
+    fn diff_lists_changed_fields_with_nested_values_per_entry() {
+        let old = calculated(loan(date(2024, 1, 1), date(2025, 1, 1)));
+        let mut new = old.clone();
+        new.margin = dec!(0.015);
+        new.base_rate_schedule.insert(date(2024, 7, 1), dec!(0.045));
+        new.facility = Some(Facility {
+            limit: dec!(2000000),
+            commitment_fee_rate: dec!(0.005),
+        });
+        let new = calculated(new);
+
+        // the recalculated totals and daily information are not amendments
+        assert_eq!(
+            diff(&old, &new).unwrap(),
+            [
+                change("base_rate_schedule.2024-07-01", "none", "0.045"),
+                change("facility.commitment_fee_rate", "none", "0.005"),
+                change("facility.limit", "none", "2000000"),
+                change("margin", "0.01", "0.015"),
+            ]
+        );
+        assert!(diff(&new, &new.clone()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn versions_are_numbered_and_kept_without_daily_information() {
+        let mut history = Vec::new();
+        let loan = calculated(loan(date(2024, 1, 1), date(2025, 1, 1)));
+        record(&mut history, Amendment::Created, "alice", None, &loan).unwrap();
+        let mut updated = loan.clone();
+        updated.end_date = date(2026, 1, 1);
+        record(&mut history, Amendment::Updated, "bob", Some(&loan), &updated).unwrap();
+
+        assert_eq!(history.iter().map(|version| version.version).collect::<Vec<_>>(), [1, 2]);
+        assert!(history[0].changes.is_empty());
+        assert_eq!(history[1].changed_by, "bob");
+        assert_eq!(history[1].changes, [change("end_date", "2025-01-01", "2026-01-01")]);
+        assert!(history[1].loan.daily_information.is_empty());
+    }
+}
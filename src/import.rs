@@ -0,0 +1,173 @@
+use anyhow::{anyhow, Context, Error};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::fs;
+use std::path::Path;
+
+use crate::{Loan, LoanCalculator};
+
+const REQUIRED_COLUMNS: [&str; 6] = ["start_date", "end_date", "amount", "currency", "base_rate", "margin"];
+
+/// Outcome of importing a CSV file, rows are identified by their line number in the file.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub imported: Vec<(usize, u32)>,
+    pub rejected: Vec<(usize, String)>,
+}
+
+/// Imports loans from a CSV file with the header
+/// `start_date,end_date,amount,currency,base_rate,margin[,day_count][,compounding]` where rates are in %.
+/// Every row is checked and calculated, rows with errors are reported and skipped without stopping the import.
+pub fn import_csv(path: &Path, calculator: &mut LoanCalculator) -> Result<ImportReport, Error> {
+    let contents = fs::read_to_string(path).with_context(|| format!("Could not read {}", path.display()))?;
+    let mut lines = contents.lines().enumerate();
+    let header: Vec<String> = lines
+        .next()
+        .ok_or(anyhow!("{} is empty.", path.display()))?
+        .1
+        .split(',')
+        .map(|column| column.trim().to_lowercase())
+        .collect();
+    if let Some(missing) = REQUIRED_COLUMNS.iter().find(|column| !header.iter().any(|name| name == *column)) {
+        return Err(anyhow!("Missing column {}. Required columns are {}.", missing, REQUIRED_COLUMNS.join(",")));
+    }
+
+    let mut report = ImportReport::default();
+    let mut loans = Vec::new();
+    for (index, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_number = index + 1;
+        match parse_row(&header, line) {
+            Ok(loan) => loans.push((line_number, loan)),
+            Err(e) => report.rejected.push((line_number, e.to_string())),
+        }
+    }
+    let (line_numbers, loans): (Vec<usize>, Vec<Loan>) = loans.into_iter().unzip();
+    for (line_number, result) in line_numbers.into_iter().zip(calculator.add_loans(loans)?) {
+        match result {
+            Ok(loan_id) => report.imported.push((line_number, loan_id)),
+            Err(e) => report.rejected.push((line_number, e.to_string())),
+        }
+    }
+    report.rejected.sort_by_key(|(line_number, _)| *line_number);
+    Ok(report)
+}
+
+/// Builds a loan from one row, collecting every invalid field rather than stopping at the first.
+fn parse_row(header: &[String], line: &str) -> Result<Loan, Error> {
+    let values: Vec<&str> = line.split(',').map(str::trim).collect();
+    if values.len() != header.len() {
+        return Err(anyhow!("expected {} columns but found {}", header.len(), values.len()));
+    }
+    let mut loan = Loan::new();
+    let mut errors = Vec::new();
+    let mut day_count_given = false;
+    for (column, value) in header.iter().zip(values) {
+        let result = match column.as_str() {
+            "start_date" => parse_date(value).map(|date| loan.start_date = date),
+            "end_date" => parse_date(value).map(|date| loan.end_date = date),
+            "amount" => value.parse::<Decimal>().map(|amount| loan.loan_amount = amount).map_err(Error::from),
+            "currency" => value.parse().map(|currency| loan.loan_currency = currency),
+            // divide by 100 to convert to %
+            "base_rate" => value.parse::<Decimal>().map(|rate| loan.base_interest_rate = rate / dec!(100)).map_err(Error::from),
+            "margin" => value.parse::<Decimal>().map(|rate| loan.margin = rate / dec!(100)).map_err(Error::from),
+            "day_count" if !value.is_empty() => {
+                day_count_given = true;
+                value.parse().map(|convention| loan.day_count_convention = convention)
+            }
+            "compounding" if !value.is_empty() => value.parse().map(|compounding| loan.compounding = compounding),
+            // unknown columns and blank optional columns are ignored
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            errors.push(format!("{}: {}", column, e));
+        }
+    }
+    // without a day_count the currency's market convention applies
+    if !day_count_given {
+        loan.day_count_convention = loan.loan_currency.default_day_count();
+    }
+    if errors.is_empty() {
+        Ok(loan)
+    } else {
+        Err(anyhow!(errors.join("; ")))
+    }
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, Error> {
+    Ok(NaiveDate::parse_from_str(value, "%Y-%m-%d")?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day_count::DayCountConvention;
+
+    /// Imports `contents` from a temporary file named after the test.
+    fn import(name: &str, contents: &str, calculator: &mut LoanCalculator) -> Result<ImportReport, Error> {
+        let path = std::env::temp_dir().join(format!("interest_app_{}_{}.csv", name, std::process::id()));
+        fs::write(&path, contents).unwrap();
+        let report = import_csv(&path, calculator);
+        fs::remove_file(&path).unwrap();
+        report
+    }
+
+    #[test]
+    fn each_row_is_imported_or_rejected_with_its_line_number() {
+        let mut calculator = LoanCalculator::new();
+        let report = import(
+            "rows",
+            "start_date,end_date,amount,currency,base_rate,margin,day_count\n\
+             2024-01-01,2025-01-01,1000000,GBP,5,1,\n\
+             2024-13-01,2025-01-01,1000000,XXX,5,1,\n\
+             \n\
+             2024-01-01,2025-01-01,500000,USD,4,1,30/360 US\n\
+             2025-01-01,2024-01-01,1000000,EUR,3,1,\n\
+             2024-01-01,2025-01-01,1000000\n",
+            &mut calculator,
+        )
+        .unwrap();
+
+        assert_eq!(report.imported, [(2, 1), (5, 2)]);
+        let rejected: Vec<usize> = report.rejected.iter().map(|(line_number, _)| *line_number).collect();
+        assert_eq!(rejected, [3, 6, 7]);
+        // every invalid field of a row is reported together
+        assert!(report.rejected[0].1.contains("start_date") && report.rejected[0].1.contains("currency"));
+        assert!(report.rejected[1].1.contains("end_date"));
+        assert_eq!(report.rejected[2].1, "expected 7 columns but found 3");
+        assert_eq!(calculator.loans().len(), 2);
+    }
+
+    #[test]
+    fn day_count_defaults_to_the_currency_convention() {
+        let mut calculator = LoanCalculator::new();
+        let report = import(
+            "day_count",
+            "start_date,end_date,amount,currency,base_rate,margin,day_count\n\
+             2024-01-01,2025-01-01,1000000,GBP,5,1,\n\
+             2024-01-01,2025-01-01,1000000,EUR,5,1,\n\
+             2024-01-01,2025-01-01,1000000,GBP,5,1,ACT/360\n",
+            &mut calculator,
+        )
+        .unwrap();
+        assert!(report.rejected.is_empty());
+        let conventions: Vec<DayCountConvention> =
+            calculator.loans().values().map(|loan| loan.day_count_convention).collect();
+        assert_eq!(
+            conventions,
+            [DayCountConvention::Act365Fixed, DayCountConvention::Act360, DayCountConvention::Act360]
+        );
+        let loan = calculator.get_loan(1).unwrap();
+        assert_eq!((loan.base_interest_rate, loan.margin), (dec!(0.05), dec!(0.01)));
+    }
+
+    #[test]
+    fn missing_columns_reject_the_whole_file() {
+        let header = "start_date,end_date,amount,currency,base_rate\n";
+        let error = import("missing", header, &mut LoanCalculator::new()).unwrap_err();
+        assert!(error.to_string().starts_with("Missing column margin."));
+    }
+}
@@ -0,0 +1,63 @@
+//! Interest accrual and amortization engine for a book of loans.
+//!
+//! A [`LoanCalculator`] holds the loans, their amendment history and the market data they are priced
+//! with. Every operation returns a `Result`, with [`LoanNotFound`] and
+//! [`validation::ValidationErrors`] as the errors callers are most likely to handle.
+//!
+//! ```
+//! use interest_app::{Loan, LoanCalculator};
+//! use rust_decimal_macros::dec;
+//!
+//! let mut calculator = LoanCalculator::new();
+//! let mut loan = Loan::new();
+//! loan.loan_amount = dec!(250000);
+//! let loan_id = calculator.add_loan(loan)?;
+//! println!("{}", calculator.get_loan(loan_id)?.total_interest);
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+pub mod calendar;
+mod calculator;
+pub mod compounding;
+pub mod currency;
+pub mod day_count;
+pub mod export;
+pub mod facility;
+pub mod fixings;
+pub mod fx;
+pub mod history;
+pub mod import;
+mod loan;
+pub mod overdue;
+pub mod payoff;
+pub mod portfolio;
+pub mod query;
+pub mod repayment;
+pub mod report;
+pub mod schedule;
+pub mod server;
+pub mod storage;
+pub mod validation;
+#[cfg(test)]
+mod test_support;
+
+pub use calculator::{LoanCalculator, LoanNotFound, MarketData, MarketDataLoad};
+pub use loan::{AccruedInterest, DailyInformation, Loan, RoundingPolicy};
+
+use anyhow::{anyhow, Error};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+/// Parses comma separated `YYYY-MM-DD=value` entries, e.g. base rate resets or custom repayments.
+pub fn parse_dated_values(input: &str) -> Result<BTreeMap<NaiveDate, Decimal>, Error> {
+    let mut values = BTreeMap::new();
+    for entry in input.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (date, value) = entry
+            .split_once('=')
+            .ok_or(anyhow!("Invalid entry '{}'. Expected YYYY-MM-DD=value.", entry))?;
+        let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")?;
+        values.insert(date, value.trim().parse::<Decimal>()?);
+    }
+    Ok(values)
+}
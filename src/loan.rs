@@ -0,0 +1,521 @@
+use anyhow::{anyhow, Error};
+use chrono::{Duration, NaiveDate};
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Bound;
+use std::str::FromStr;
+
+use crate::calendar::{HolidayCalendar, RollConvention};
+use crate::compounding::{Compounding, CompoundingAccrual};
+use crate::currency::Currency;
+use crate::day_count::{AccrualPeriod, DayCountConvention};
+use crate::facility::{self, Facility, LedgerEvent};
+use crate::fixings::{FixingFallback, RateFixings};
+use crate::overdue::{self, OverdueEvent};
+use crate::repayment::{PaymentFrequency, RepaymentSchedule, RepaymentType};
+use crate::schedule;
+use crate::MarketData;
+
+/// How a Decimal amount is rounded at presentation or settlement boundaries.
+/// Calculations are always carried out at full precision, rounding is only applied on output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoundingPolicy {
+    /// Round half to even, e.g. 2.125 -> 2.12, 2.135 -> 2.14
+    Bankers,
+    /// Round half away from zero, e.g. 2.125 -> 2.13
+    HalfUp,
+    /// Drop any digits past the requested precision, e.g. 2.129 -> 2.12
+    Truncate,
+}
+
+impl RoundingPolicy {
+    pub fn round(&self, value: Decimal, dp: u32) -> Decimal {
+        let strategy = match self {
+            RoundingPolicy::Bankers => RoundingStrategy::MidpointNearestEven,
+            RoundingPolicy::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            RoundingPolicy::Truncate => RoundingStrategy::ToZero,
+        };
+        value.round_dp_with_strategy(dp, strategy)
+    }
+}
+
+impl FromStr for RoundingPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "bankers" => Ok(RoundingPolicy::Bankers),
+            "half-up" => Ok(RoundingPolicy::HalfUp),
+            "truncate" => Ok(RoundingPolicy::Truncate),
+            other => Err(anyhow!("Unknown rounding policy '{}'. Expected bankers, half-up or truncate.", other)),
+        }
+    }
+}
+
+impl fmt::Display for RoundingPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RoundingPolicy::Bankers => "bankers",
+            RoundingPolicy::HalfUp => "half-up",
+            RoundingPolicy::Truncate => "truncate",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+// fields left out, e.g. in a server request, take their values from Loan::new()
+#[serde(default)]
+pub struct Loan {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub loan_amount: Decimal,
+    pub loan_currency: Currency,
+    // rate that applies from the start date until the first entry in base_rate_schedule
+    pub base_interest_rate: Decimal,
+    // base rate resets keyed by the date they are effective from, used for floating rate loans
+    pub base_rate_schedule: BTreeMap<NaiveDate, Decimal>,
+    // benchmark index (e.g. SOFR) whose fixings replace the typed-in base rates when set
+    pub rate_index: Option<String>,
+    pub fixing_fallback: FixingFallback,
+    pub margin: Decimal,
+    pub total_interest: Decimal,
+    // fee on the undrawn part of a facility, kept apart from the interest on the drawn balance
+    pub total_commitment_fee: Decimal,
+    pub rounding_policy: RoundingPolicy,
+    pub day_count_convention: DayCountConvention,
+    pub compounding: Compounding,
+    pub repayment_schedule: RepaymentSchedule,
+    // how often interest is paid, None pays it with scheduled repayments, or at maturity for bullet and custom repayments
+    pub interest_frequency: Option<PaymentFrequency>,
+    // holiday calendars joined to decide business days for payment and reset dates
+    pub business_day_calendars: Vec<String>,
+    pub roll_convention: RollConvention,
+    // drawdowns and repayments on top of the initial loan amount and the repayment schedule, used for revolving facilities
+    pub ledger: Vec<LedgerEvent>,
+    pub facility: Option<Facility>,
+    // additional margin charged on overdue amounts on top of the all-in rate
+    pub default_margin: Decimal,
+    pub overdue_events: Vec<OverdueEvent>,
+    // default interest on overdue amounts up to their payment, kept apart from the interest on the outstanding principal
+    pub total_default_interest: Decimal,
+    // signed change to the drawn principal on each date, calculated from the ledger and the repayment schedule
+    pub principal_movements: BTreeMap<NaiveDate, Decimal>,
+    // This could be a vector but we may want to access daily information by date in the future.
+    // BTreeMap is used as it is sorted by key and efficient for lookups.
+    pub daily_information: BTreeMap<NaiveDate, DailyInformation>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct DailyInformation {
+    pub day_interest: Decimal,
+    pub day_interest_no_margin: Decimal,
+    // base rate that applied for the day, taken from the loan's base rate schedule or index fixings
+    pub base_rate: Decimal,
+    // principal outstanding during the day, after any drawdowns and repayments at the start of the day
+    pub outstanding_principal: Decimal,
+    // fee on the undrawn part of the facility limit, zero for term loans
+    pub commitment_fee: Decimal,
+    // default interest on amounts overdue during the day, zero when every payment was made on time
+    #[serde(default)]
+    pub default_interest: Decimal,
+    // balance interest is charged on at the end of the day, after any capitalisation
+    pub capitalised_balance: Decimal,
+    pub days_elapsed: i64,
+}
+
+/// Interest and fees accrued over a number of days of a loan, summed from its daily information.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct AccruedInterest {
+    pub days: i64,
+    pub interest: Decimal,
+    pub interest_no_margin: Decimal,
+    pub commitment_fee: Decimal,
+    pub default_interest: Decimal,
+}
+
+impl Default for Loan {
+    fn default() -> Self {
+        Loan::new()
+    }
+}
+
+/// Create a method new() for the Loan struct that takes in no values and returns a Loan with default values.
+impl Loan {
+    /// A 1000 USD loan from 2020-01-01 to 2020-01-05 at 5% plus a 1% margin, used as the starting point for new loans.
+    pub fn new() -> Self {
+        Loan {
+            loan_amount: dec!(1000),
+            start_date: NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2020, 1, 5).unwrap(),
+            loan_currency: Currency::USD,
+            base_interest_rate: dec!(0.05),
+            base_rate_schedule: BTreeMap::new(),
+            rate_index: None,
+            fixing_fallback: FixingFallback::PreviousBusinessDay,
+            margin: dec!(0.01),
+            total_interest: Decimal::ZERO,
+            total_commitment_fee: Decimal::ZERO,
+            rounding_policy: RoundingPolicy::Bankers,
+            day_count_convention: Currency::USD.default_day_count(),
+            compounding: Compounding::Simple,
+            repayment_schedule: RepaymentSchedule::bullet(),
+            interest_frequency: None,
+            business_day_calendars: Vec::new(),
+            roll_convention: RollConvention::Unadjusted,
+            ledger: Vec::new(),
+            facility: None,
+            default_margin: Decimal::ZERO,
+            overdue_events: Vec::new(),
+            total_default_interest: Decimal::ZERO,
+            principal_movements: BTreeMap::new(),
+            daily_information: BTreeMap::new(),
+        }
+    }
+
+    /// Average base rate over the term, which is the base rate itself for fixed rate loans.
+    pub fn average_base_rate(&self) -> Decimal {
+        if self.daily_information.is_empty() {
+            return self.base_interest_rate;
+        }
+        let days = Decimal::from(self.daily_information.len());
+        self.daily_information.values().map(|daily| daily.base_rate).sum::<Decimal>() / days
+    }
+
+    /// Average base rate plus the margin.
+    pub fn all_in_rate(&self) -> Decimal {
+        self.average_base_rate() + self.margin
+    }
+
+    /// Principal drawn at the end of `date`, after that day's drawdowns and repayments. Zero before the start date.
+    pub fn outstanding_principal(&self, date: NaiveDate) -> Decimal {
+        if date < self.start_date {
+            return Decimal::ZERO;
+        }
+        self.loan_amount + self.principal_movements.range(..=date).map(|(_, movement)| movement).sum::<Decimal>()
+    }
+
+    /// How often interest is paid, None when it is only paid at maturity.
+    fn interest_payment_frequency(&self) -> Option<PaymentFrequency> {
+        match (self.interest_frequency, self.repayment_schedule.repayment_type) {
+            (Some(frequency), _) => Some(frequency),
+            (None, RepaymentType::Bullet | RepaymentType::Custom) => None,
+            (None, RepaymentType::EqualPrincipal | RepaymentType::Annuity | RepaymentType::Balloon) => {
+                Some(self.repayment_schedule.frequency)
+            }
+        }
+    }
+
+    /// Dates interest is paid on, rolled onto business days of `calendar`. The end date is always one of them.
+    pub fn interest_payment_dates(&self, calendar: &HolidayCalendar) -> Vec<NaiveDate> {
+        match self.interest_payment_frequency() {
+            Some(frequency) => schedule::adjusted_payment_dates(
+                self.start_date,
+                self.end_date,
+                frequency,
+                self.repayment_schedule.stub,
+                calendar,
+                self.roll_convention,
+            ),
+            None => vec![self.end_date],
+        }
+    }
+
+    /// Interest periods from the start date to each interest payment date, the reference periods the day count
+    /// convention measures year fractions within.
+    pub fn accrual_periods(&self, calendar: &HolidayCalendar) -> Vec<AccrualPeriod> {
+        let frequency = self.interest_payment_frequency().map_or(1, |frequency| frequency.periods_per_year());
+        let mut start = self.start_date;
+        self.interest_payment_dates(calendar)
+            .into_iter()
+            .map(|end| {
+                let period = AccrualPeriod {
+                    start,
+                    end,
+                    frequency,
+                    maturity: self.end_date,
+                };
+                start = end;
+                period
+            })
+            .collect()
+    }
+
+    /// Interest accrued from the start date up to `date`, the day ending on `date` included.
+    /// Zero before the start date and the whole term's accrual from the end date on, apart from default interest
+    /// which keeps accruing after maturity on amounts still overdue.
+    pub fn accrued_to(&self, date: NaiveDate) -> AccruedInterest {
+        let mut accrued = accrued(self.daily_information.range(..=date).map(|(_, daily)| daily));
+        accrued.default_interest += self.default_interest_after_maturity(Some(date));
+        accrued
+    }
+
+    /// Interest accrued on the days from `from` to `to`, the day starting on `from` and the day ending on `to` included.
+    /// Days outside the loan term accrue nothing.
+    pub fn accrued_between(&self, from: NaiveDate, to: NaiveDate) -> Result<AccruedInterest, Error> {
+        if to < from {
+            return Err(anyhow!("The end of the period {} is before its start {}.", to, from));
+        }
+        // daily information is keyed by the date each day's accrual ends on
+        let mut accrued = accrued(self.daily_information.range((Bound::Excluded(from), Bound::Included(to))).map(|(_, daily)| daily));
+        accrued.default_interest +=
+            self.default_interest_after_maturity(Some(to)) - self.default_interest_after_maturity(Some(from));
+        Ok(accrued)
+    }
+
+    /// Default interest on amounts overdue after the end date, charged at the last day's all-in rate plus the
+    /// default margin until each amount is paid or `to`, whichever is earlier. Without `to` only paid amounts are counted.
+    fn default_interest_after_maturity(&self, to: Option<NaiveDate>) -> Decimal {
+        let base_rate = self.daily_information.values().next_back().map_or(self.base_interest_rate, |daily| daily.base_rate);
+        self.overdue_events
+            .iter()
+            .filter_map(|event| {
+                let from = event.due_date.max(self.end_date);
+                let until = match (event.paid_date, to) {
+                    (Some(paid_date), Some(to)) => paid_date.min(to),
+                    (Some(paid_date), None) => paid_date,
+                    (None, Some(to)) => to,
+                    (None, None) => return None,
+                };
+                // each overdue amount after maturity is its own accrual period
+                let accrual_period = AccrualPeriod {
+                    start: from,
+                    end: until,
+                    frequency: 1,
+                    maturity: self.end_date,
+                };
+                (until > from).then(|| {
+                    event.amount
+                        * (base_rate + self.margin + self.default_margin)
+                        * self.day_count_convention.year_fraction(from, until, &accrual_period)
+                })
+            })
+            .sum()
+    }
+
+    /// Returns the base rate effective on the given date.
+    /// Loans referencing a benchmark index use its fixings, otherwise the base rate schedule is used,
+    /// with reset dates rolled onto business days, falling back to base_interest_rate before the first reset.
+    fn base_rate_on(&self, date: NaiveDate, fixings: &RateFixings, calendar: &HolidayCalendar) -> Result<Decimal, Error> {
+        if let Some(index) = &self.rate_index {
+            return fixings.fixing(index, date, self.fixing_fallback, calendar);
+        }
+        Ok(self
+            .base_rate_schedule
+            .iter()
+            .rev()
+            .find(|(reset_date, _)| self.roll_convention.adjust(**reset_date, calendar) <= date)
+            .map(|(_, rate)| *rate)
+            .unwrap_or(self.base_interest_rate))
+    }
+
+    pub fn calculate_interest(&mut self, market_data: &MarketData) -> Result<(), Error> {
+        let fixings = &market_data.fixings;
+        let calendar = market_data.calendars.joint(&self.business_day_calendars)?;
+        let days = self.end_date.signed_duration_since(self.start_date).num_days();
+        let accrual_periods = self.accrual_periods(&calendar);
+        let mut accrual = CompoundingAccrual::new(self.compounding, self.loan_amount, self.start_date, self.end_date);
+        let mut accrued_interest = Decimal::ZERO;
+        // start, base rate and outstanding principal of each period both were constant for, used for the simple interest total
+        let mut rate_periods: Vec<(NaiveDate, Decimal, Decimal)> = Vec::new();
+        self.daily_information.clear();
+        // annuity payments are sized using the all-in rate at the start of the loan
+        let initial_rate = self.base_rate_on(self.start_date, fixings, &calendar)? + self.margin;
+        let repayments = self.repayment_schedule.principal_repayments(
+            self.loan_amount,
+            initial_rate,
+            self.day_count_convention,
+            &accrual_periods,
+            &calendar,
+            self.roll_convention,
+        )?;
+        if let Some(event) = self.ledger.iter().find(|event| event.date < self.start_date || event.date > self.end_date) {
+            return Err(anyhow!("Ledger event on {} is outside the loan term.", event.date));
+        }
+        // signed change to the drawn principal on each date from both the ledger and the repayment schedule
+        let mut principal_movements = facility::net_movements(&self.ledger);
+        for (date, repayment) in repayments {
+            *principal_movements.entry(date).or_insert(Decimal::ZERO) -= repayment;
+        }
+        let mut total_commitment_fee = Decimal::ZERO;
+        let mut total_default_interest = Decimal::ZERO;
+
+        // This could be done more concisely but having it structured like this allows the interest to be changed to a more complex type in the future.
+        for day in 1..days+1 {
+            let current_date = self.start_date + Duration::days(day);
+            let previous_date = current_date - Duration::days(1);
+            let day_fraction = self.day_count_convention.fraction_over(previous_date, current_date, &accrual_periods);
+            // interest for the day is charged at the rate effective at the start of the day
+            let base_rate = self.base_rate_on(previous_date, fixings, &calendar)?;
+            // principal drawn or repaid on a date accrues, or stops accruing, interest from that date
+            if let Some(movement) = principal_movements.get(&previous_date) {
+                accrual.change_principal(*movement);
+            }
+            let outstanding_principal = accrual.principal();
+            if outstanding_principal < Decimal::ZERO {
+                return Err(anyhow!("Repayments on {} are more than the drawn balance.", previous_date));
+            }
+            let commitment_fee = match &self.facility {
+                Some(facility) if outstanding_principal > facility.limit => {
+                    return Err(anyhow!(
+                        "Drawn balance {} on {} is more than the facility limit {}.",
+                        outstanding_principal,
+                        previous_date,
+                        facility.limit
+                    ));
+                }
+                Some(facility) => facility.commitment_fee(outstanding_principal, day_fraction),
+                None => Decimal::ZERO,
+            };
+            total_commitment_fee += commitment_fee;
+            let default_interest = overdue::overdue_amount(&self.overdue_events, previous_date)
+                * (base_rate + self.margin + self.default_margin)
+                * day_fraction;
+            total_default_interest += default_interest;
+            if rate_periods
+                .last()
+                .is_none_or(|(_, rate, outstanding)| *rate != base_rate || *outstanding != outstanding_principal)
+            {
+                rate_periods.push((previous_date, base_rate, outstanding_principal));
+            }
+            let (daily_interest_amount, daily_interest_amount_no_margin) =
+                accrual.accrue(current_date, base_rate, self.margin, day_fraction);
+            accrued_interest += daily_interest_amount;
+            let daily_information = DailyInformation {
+                day_interest: daily_interest_amount,
+                day_interest_no_margin: daily_interest_amount_no_margin,
+                base_rate,
+                outstanding_principal,
+                commitment_fee,
+                default_interest,
+                capitalised_balance: accrual.capitalised_balance(),
+                days_elapsed: day,
+            };
+            self.daily_information.insert(current_date, daily_information);
+        }
+        if self.compounding == Compounding::Simple {
+            // the total uses the year fraction of each rate period rather than summing the days,
+            // this is the figure counterparties quote and avoids accumulating rounding in the daily fractions
+            let mut total_interest = Decimal::ZERO;
+            for (index, (period_start, base_rate, outstanding_principal)) in rate_periods.iter().enumerate() {
+                let period_end = rate_periods.get(index + 1).map_or(self.end_date, |(date, _, _)| *date);
+                let year_fraction = self.day_count_convention.fraction_over(*period_start, period_end, &accrual_periods);
+                total_interest += outstanding_principal * (base_rate + self.margin) * year_fraction;
+            }
+            self.total_interest = total_interest;
+        } else {
+            // compounded interest depends on the path of the balance so the total is the sum of each day's accrual
+            self.total_interest = accrued_interest;
+        }
+        self.total_commitment_fee = total_commitment_fee;
+        // amounts paid after maturity are also charged for the days after the end date
+        self.total_default_interest = total_default_interest + self.default_interest_after_maturity(None);
+        // the final repayment on the end date is whatever is still drawn at maturity, e.g. on a revolving facility
+        // or a bullet loan that has been partly repaid early
+        let drawn_at_maturity = self.loan_amount + principal_movements.values().sum::<Decimal>();
+        if !drawn_at_maturity.is_zero() {
+            *principal_movements.entry(self.end_date).or_insert(Decimal::ZERO) -= drawn_at_maturity;
+        }
+        self.principal_movements = principal_movements;
+        Ok(())
+    }
+}
+
+fn accrued<'a>(days: impl Iterator<Item = &'a DailyInformation>) -> AccruedInterest {
+    days.fold(AccruedInterest::default(), |total, daily| AccruedInterest {
+        days: total.days + 1,
+        interest: total.interest + daily.day_interest,
+        interest_no_margin: total.interest_no_margin + daily.day_interest_no_margin,
+        commitment_fee: total.commitment_fee + daily.commitment_fee,
+        default_interest: total.default_interest + daily.default_interest,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{calculated, date, loan};
+
+    #[test]
+    fn daily_interest_adds_up_to_the_total_on_thirty_360() {
+        let mut loan = loan(date(2023, 1, 1), date(2024, 1, 1));
+        loan.day_count_convention = DayCountConvention::Thirty360Us;
+        let loan = calculated(loan);
+
+        assert_eq!(loan.total_interest.round_dp(2), dec!(60000));
+        let accrued = loan.accrued_to(loan.end_date);
+        assert_eq!(accrued.interest.round_dp(2), dec!(60000));
+        assert_eq!((accrued.interest - accrued.interest_no_margin).round_dp(2), dec!(10000));
+    }
+
+    #[test]
+    fn accrued_between_counts_the_days_after_from_up_to_to() {
+        // 6% on 1,000,000 ACT/360 accrues 166.67 a day
+        let loan = calculated(loan(date(2024, 1, 1), date(2024, 2, 1)));
+        let daily = dec!(1000000) * dec!(0.06) / dec!(360);
+        let days = |from, to| loan.accrued_between(from, to).unwrap().interest;
+
+        assert_eq!(days(date(2024, 1, 10), date(2024, 1, 10)), Decimal::ZERO);
+        assert_eq!(days(date(2024, 1, 10), date(2024, 1, 11)).round_dp(10), daily.round_dp(10));
+        assert_eq!(days(date(2024, 1, 10), date(2024, 1, 20)).round_dp(10), (daily * dec!(10)).round_dp(10));
+        // consecutive periods add up to the whole term without counting a day twice
+        assert_eq!(
+            (days(loan.start_date, date(2024, 1, 15)) + days(date(2024, 1, 15), loan.end_date)).round_dp(10),
+            loan.total_interest.round_dp(10)
+        );
+        assert!(loan.accrued_between(date(2024, 1, 20), date(2024, 1, 10)).is_err());
+    }
+
+    #[test]
+    fn accrued_between_ignores_days_outside_the_term() {
+        let loan = calculated(loan(date(2024, 1, 1), date(2024, 2, 1)));
+        let interest = |from, to| loan.accrued_between(from, to).unwrap().interest;
+
+        assert_eq!(interest(date(2023, 11, 1), date(2023, 12, 31)), Decimal::ZERO);
+        assert_eq!(interest(date(2023, 11, 1), loan.start_date), Decimal::ZERO);
+        assert_eq!(interest(date(2023, 11, 1), date(2024, 1, 5)), loan.accrued_to(date(2024, 1, 5)).interest);
+        assert_eq!(interest(date(2024, 1, 20), date(2024, 3, 1)), interest(date(2024, 1, 20), loan.end_date));
+        assert_eq!(interest(loan.end_date, date(2024, 3, 1)), Decimal::ZERO);
+        assert_eq!(interest(date(2023, 11, 1), date(2024, 3, 1)), loan.total_interest);
+    }
+
+    #[test]
+    fn default_interest_accrues_after_maturity() {
+        let mut loan = loan(date(2024, 1, 1), date(2024, 2, 1));
+        loan.default_margin = dec!(0.02);
+        loan.overdue_events = vec![OverdueEvent {
+            due_date: loan.end_date,
+            amount: dec!(1000),
+            paid_date: None,
+        }];
+        let mut loan = calculated(loan);
+        assert_eq!(loan.total_default_interest, Decimal::ZERO);
+        // 36 days at 8% ACT/360
+        let expected = dec!(1000) * dec!(0.08) * dec!(36) / dec!(360);
+        assert_eq!(loan.accrued_to(date(2024, 3, 8)).default_interest.round_dp(10), expected.round_dp(10));
+        assert_eq!(loan.accrued_between(loan.end_date, date(2024, 3, 8)).unwrap().default_interest.round_dp(10), expected.round_dp(10));
+
+        loan.overdue_events[0].paid_date = Some(date(2024, 3, 8));
+        let loan = calculated(loan);
+        assert_eq!(loan.total_default_interest.round_dp(10), expected.round_dp(10));
+        assert_eq!(loan.accrued_to(date(2024, 12, 31)).default_interest.round_dp(10), expected.round_dp(10));
+    }
+
+    #[test]
+    fn act_365l_measures_each_interest_period_on_its_own() {
+        let mut loan = loan(date(2023, 1, 1), date(2025, 1, 1));
+        loan.base_interest_rate = dec!(0.06);
+        loan.margin = Decimal::ZERO;
+        loan.day_count_convention = DayCountConvention::Act365L;
+        loan.interest_frequency = Some(PaymentFrequency::Annual);
+        let loan = calculated(loan);
+
+        // 2023 has 365 days over 365 and 2024, holding 29 February, 366 over 366
+        assert_eq!(loan.total_interest.round_dp(2), dec!(120000));
+        assert_eq!(loan.accrued_to(date(2024, 1, 1)).interest.round_dp(2), dec!(60000));
+        assert_eq!(loan.accrued_to(loan.end_date).interest.round_dp(2), dec!(120000));
+    }
+}
@@ -1,105 +1,74 @@
-use chrono::{Duration, NaiveDate};
+mod cli;
+mod display;
+
+use chrono::{Local, NaiveDate};
+use clap::Parser;
+use cli::Cli;
+use interest_app::currency::Currency;
+use interest_app::export::{self, ExportFormat};
+use interest_app::facility::{self, Facility};
+use interest_app::import;
+use interest_app::overdue;
+use interest_app::payoff::BreakCost;
+use interest_app::portfolio;
+use interest_app::query::{self, LoanFilter, SortKey};
+use interest_app::repayment::RepaymentType;
+use interest_app::report::{self, Granularity, ReportOptions};
+use interest_app::schedule;
+use interest_app::storage::JsonStorage;
+use interest_app::validation::ValidationRules;
+use interest_app::{parse_dated_values, Loan, LoanCalculator, MarketDataLoad};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 use std::collections::BTreeMap;
 use std::io::{self, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::str::FromStr;
 use anyhow::{anyhow, Error};
 
-#[derive(Clone, Debug)]
-struct Loan {
-    start_date: NaiveDate,
-    end_date: NaiveDate,
-    loan_amount: f64,
-    loan_currency: String,
-    base_interest_rate: f64,
-    margin: f64,
-    total_interest: f64,
-    // This could be a vector but we may want to access daily information by date in the future.
-    // BTreeMap is used as it is sorted by key and efficient for lookups.
-    daily_information: BTreeMap<NaiveDate, Daily_Information>,
-}
-
-#[derive(Clone, Copy, Debug)]
-#[allow(non_camel_case_types)]
-struct Daily_Information {
-    day_interest: f64,
-    day_interest_no_margin: f64,
-    days_elapsed: i64,
-}
-
-/// Create a method new() for the Loan struct that takes in no values and returns a Loan with default values.
-impl Loan {
-    fn new() -> Self {
-        Loan {
-            loan_amount: 1000.0,
-            start_date: NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
-            end_date: NaiveDate::from_ymd_opt(2020, 1, 5).unwrap(),
-            loan_currency: "USD".to_string(),
-            base_interest_rate: 0.05,
-            margin: 0.01,
-            total_interest: 0.0,
-            daily_information: BTreeMap::new(),
-        }
-    }
-    fn calculate_interest(&mut self) -> () {
-        let days = self.end_date.signed_duration_since(self.start_date).num_days();
-        let total_interest_rate = self.base_interest_rate + self.margin;
-        let daily_interest_rate_no_margin = self.base_interest_rate / 365.0;
-        let daily_interest_rate = total_interest_rate / 365.0;
-
-        // This could be done more concisely but having it structured like this allows the interest to be changed to a more complex type in the future.
-        for day in 1..days+1 {
-            let current_date = self.start_date + Duration::days(day);
-            let daily_interest_amount_no_margin = self.loan_amount * daily_interest_rate_no_margin;
-            let daily_interest_amount = self.loan_amount * daily_interest_rate;
-            let daily_information = Daily_Information {
-                day_interest: daily_interest_amount,
-                day_interest_no_margin: daily_interest_amount_no_margin,
-                days_elapsed: day,
-            };
-            self.daily_information.insert(current_date, daily_information);
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            cli::exit_code(&e)
         }
-        let total_interest: f64 = self.loan_amount * daily_interest_rate * days as f64;
-        self.total_interest = total_interest;
     }
 }
 
-#[derive(Debug)]
-struct LoanCalculator {
-    // BTreeMap is used as it is ordered by key and efficient for lookups.
-    // HashMap could be used for faster lookups but it is unordered so we do not use it here.
-    loans: BTreeMap<u32, Loan>,
-    next_loan_id: u32,
-}
-
-impl LoanCalculator {
-    fn new() -> Self {
-        LoanCalculator {
-            loans: BTreeMap::new(),
-            next_loan_id: 1,
-        }
+fn run(cli: Cli) -> Result<(), Error> {
+    let mut calculator = LoanCalculator::open(JsonStorage::new(&cli.book))?;
+    if let Some(user) = cli.user {
+        calculator.set_user(user);
     }
-
-    fn add_loan(&mut self, loan: Loan) -> u32 {
-        let loan_id = self.next_loan_id;
-        self.loans.insert(loan_id, loan);
-        self.next_loan_id += 1;
-        loan_id
+    calculator.set_validation_rules(ValidationRules {
+        allow_negative_rates: cli.allow_negative_rates,
+    });
+    for path in cli.fixings.iter() {
+        print_failed_loans(calculator.load_fixings(path)?);
     }
-
-    fn update_loan(&mut self, loan_id: u32, updated_loan: Loan) -> Result<(), Error> {
-        if self.loans.contains_key(&loan_id) {
-            self.loans.insert(loan_id, updated_loan);
-            println!("Loan with ID {} updated successfully!\n", loan_id);
-            Ok(())
-        } else {
-            Err(anyhow!("Loan with ID {} not found.\n", loan_id))
-        }
+    for path in cli.fx_rates.iter() {
+        calculator.load_fx_rates(path)?;
+    }
+    for calendar in cli.calendar.iter() {
+        let (name, path) = calendar
+            .split_once('=')
+            .ok_or(anyhow!("Invalid calendar '{}'. Expected NAME=FILE.", calendar))?;
+        print_failed_loans(calculator.load_calendar(name, Path::new(path))?);
+    }
+    match cli.command {
+        Some(command) => cli::run_command(command, &mut calculator),
+        None => run_menu(&mut calculator),
     }
 }
 
-fn main() -> Result<(), Error>{
+fn run_menu(calculator: &mut LoanCalculator) -> Result<(), Error> {
     println!("Loan Interest Calculator");
-
-    let mut calculator = LoanCalculator::new();
+    if let Some(storage) = calculator.storage() {
+        println!("Loan book: {} ({} loans)", storage.path().display(), calculator.loans().len());
+    }
 
     loop {
         println!("------------------------");
@@ -107,7 +76,22 @@ fn main() -> Result<(), Error>{
         println!("2. Update Loan");
         println!("3. Show Loan Information");
         println!("4. Show All Loans");
-        println!("5. Exit");
+        println!("5. Load Rate Fixings");
+        println!("6. Show Amortization Schedule");
+        println!("7. Load Holiday Calendar");
+        println!("8. Import Loans from CSV");
+        println!("9. Export Daily Accruals");
+        println!("10. Delete Loan");
+        println!("11. Archive Loan");
+        println!("12. Restore Archived Loan");
+        println!("13. Show Loan History");
+        println!("14. Recalculate Loan As Of Version");
+        println!("15. Load FX Rates");
+        println!("16. Show Portfolio Totals");
+        println!("17. Search Loans");
+        println!("18. Show Accrued Interest");
+        println!("19. Payoff Quote");
+        println!("20. Exit");
         print!("Please enter your choice: ");
         io::stdout().flush().unwrap();
 
@@ -115,26 +99,71 @@ fn main() -> Result<(), Error>{
         io::stdin().read_line(&mut choice).unwrap();
 
         // quick error handling for non integer input
-        let choice: u32 = choice.trim().parse::<u32>().or::<u32>(Ok(999)).unwrap();
+        let choice: u32 = choice.trim().parse::<u32>().unwrap_or(999);
         let result = match choice {
             1 => {
-                add_loan(&mut calculator)
+                add_loan(calculator)
             }
             2 => {
-                update_loan(&mut calculator)   
+                update_loan(calculator)   
             }
             3 => {
-                show_loan_information(&mut calculator)
+                show_loan_information(calculator)
             }
             4 => {
-                show_all_loans(&mut calculator)
+                show_all_loans(calculator)
             }
             5 => {
+                load_fixings(calculator)
+            }
+            6 => {
+                show_amortization_schedule(calculator)
+            }
+            7 => {
+                load_calendar(calculator)
+            }
+            8 => {
+                import_loans(calculator)
+            }
+            9 => {
+                export_accruals(calculator)
+            }
+            10 => {
+                delete_loan(calculator)
+            }
+            11 => {
+                archive_loan(calculator)
+            }
+            12 => {
+                restore_loan(calculator)
+            }
+            13 => {
+                show_loan_history(calculator)
+            }
+            14 => {
+                recalculate_loan_as_of_version(calculator)
+            }
+            15 => {
+                load_fx_rates(calculator)
+            }
+            16 => {
+                show_portfolio_totals(calculator)
+            }
+            17 => {
+                search_loans(calculator)
+            }
+            18 => {
+                show_accrued_interest(calculator)
+            }
+            19 => {
+                payoff_quote(calculator)
+            }
+            20 => {
                 println!("Exiting...");
                 break
             }
             _ => {
-                println!("\nInvalid choice! Please enter an integer from 1-5.");
+                println!("\nInvalid choice! Please enter an integer from 1-20.");
                 Ok(())
             }
         };
@@ -147,9 +176,12 @@ fn main() -> Result<(), Error>{
 
 fn show_all_loans(calculator: &mut LoanCalculator) -> Result<(), Error> {
     println!("All Loans:");
-    for (loan_id, loan) in calculator.loans.iter() {
+    for (loan_id, loan) in calculator.loans().iter() {
         println!("Loan ID: {}", loan_id);
-        println!("{:#?}\n", loan);
+        for line in report::summary(loan) {
+            println!("{}", line);
+        }
+        println!();
     }
     Ok(())
 }
@@ -160,48 +192,379 @@ fn update_loan(calculator: &mut LoanCalculator) -> Result<(), Error> {
     let mut loan_id_input = String::new();
     io::stdin().read_line(&mut loan_id_input).unwrap();
     let loan_id: u32 = loan_id_input.trim().parse()?;
-    if let Some(mut loan) = calculator.loans.get(&loan_id).cloned() {
-        // reset total_interest to 0 so it can be recalculated
-        loan.total_interest = 0.0;
-        // reset daily_information to empty so it can be recalculated
-        loan.daily_information = BTreeMap::new();
-        loan = update_loan_parameters(loan)?;
-        calculator.update_loan(loan_id, loan)?;
-        let new_loan = calculator.loans.get_mut(&loan_id).unwrap();
-        new_loan.calculate_interest();
-        Ok(())
+    let mut loan = calculator.get_loan(loan_id)?.clone();
+    // reset total_interest to 0 so it can be recalculated
+    loan.total_interest = Decimal::ZERO;
+    loan.total_commitment_fee = Decimal::ZERO;
+    // reset daily_information to empty so it can be recalculated
+    loan.daily_information = BTreeMap::new();
+    loan = update_loan_parameters(loan)?;
+    calculator.update_loan(loan_id, loan)?;
+    println!("Loan with ID {} updated successfully!\n", loan_id);
+    Ok(())
+}
+
+fn delete_loan(calculator: &mut LoanCalculator) -> Result<(), Error> {
+    print!("Enter the Loan ID to delete: ");
+    io::stdout().flush().unwrap();
+    let mut loan_id_input = String::new();
+    io::stdin().read_line(&mut loan_id_input).unwrap();
+    let loan_id: u32 = loan_id_input.trim().parse()?;
+
+    print!("Permanently delete loan {}? Archive it instead to keep it for audit. (y/n): ", loan_id);
+    io::stdout().flush().unwrap();
+    let mut confirm = String::new();
+    io::stdin().read_line(&mut confirm).unwrap();
+    if confirm.trim().eq_ignore_ascii_case("y") {
+        calculator.delete_loan(loan_id)?;
+        println!("Loan with ID {} deleted.\n", loan_id);
     } else {
-        Err(anyhow!("Loan with ID {} not found.\n", loan_id))
+        println!("Loan with ID {} kept.\n", loan_id);
     }
+    Ok(())
 }
 
-fn show_loan_information(calculator: &mut LoanCalculator) -> Result<(), Error>{
+fn archive_loan(calculator: &mut LoanCalculator) -> Result<(), Error> {
+    print!("Enter the Loan ID to archive: ");
+    io::stdout().flush().unwrap();
+    let mut loan_id_input = String::new();
+    io::stdin().read_line(&mut loan_id_input).unwrap();
+    let loan_id: u32 = loan_id_input.trim().parse()?;
+
+    calculator.archive_loan(loan_id)?;
+    println!("Loan with ID {} archived.\n", loan_id);
+    Ok(())
+}
+
+fn restore_loan(calculator: &mut LoanCalculator) -> Result<(), Error> {
+    if calculator.archived_loans().is_empty() {
+        println!("There are no archived loans.\n");
+        return Ok(());
+    }
+    println!("Archived Loans:");
+    for (loan_id, loan) in calculator.archived_loans().iter() {
+        println!(
+            "{}: {} from {} to {}",
+            loan_id,
+            report::format_amount(loan.loan_amount, loan.loan_currency, loan.rounding_policy),
+            loan.start_date,
+            loan.end_date
+        );
+    }
+    print!("Enter the Loan ID to restore: ");
+    io::stdout().flush().unwrap();
+    let mut loan_id_input = String::new();
+    io::stdin().read_line(&mut loan_id_input).unwrap();
+    let loan_id: u32 = loan_id_input.trim().parse()?;
+
+    calculator.restore_loan(loan_id)?;
+    println!("Loan with ID {} restored.\n", loan_id);
+    Ok(())
+}
+
+fn show_loan_history(calculator: &mut LoanCalculator) -> Result<(), Error> {
     print!("Enter the Loan ID: ");
     io::stdout().flush().unwrap();
     let mut loan_id_input = String::new();
     io::stdin().read_line(&mut loan_id_input).unwrap();
     let loan_id: u32 = loan_id_input.trim().parse()?;
 
-    let loan = calculator.loans.get_mut(&loan_id).ok_or(anyhow!("Loan with ID {} not found.\n", loan_id))?;
-    print_interest_results(loan.clone());
+    display::print_history(loan_id, calculator.loan_history(loan_id)?);
     Ok(())
 }
 
+fn recalculate_loan_as_of_version(calculator: &mut LoanCalculator) -> Result<(), Error> {
+    print!("Enter the Loan ID: ");
+    io::stdout().flush().unwrap();
+    let mut loan_id_input = String::new();
+    io::stdin().read_line(&mut loan_id_input).unwrap();
+    let loan_id: u32 = loan_id_input.trim().parse()?;
+
+    print!("Enter the version: ");
+    io::stdout().flush().unwrap();
+    let mut version_input = String::new();
+    io::stdin().read_line(&mut version_input).unwrap();
+    let version: u32 = version_input.trim().parse()?;
+
+    let loan = calculator.loan_as_of_version(loan_id, version)?;
+    println!("Loan ID {} as of version {}", loan_id, version);
+    display::print_report(&loan, &ReportOptions::default())
+}
+
+fn show_loan_information(calculator: &mut LoanCalculator) -> Result<(), Error>{
+    print!("Enter the Loan ID: ");
+    io::stdout().flush().unwrap();
+    let mut loan_id_input = String::new();
+    io::stdin().read_line(&mut loan_id_input).unwrap();
+    let loan_id: u32 = loan_id_input.trim().parse()?;
+
+    let loan = calculator.get_loan(loan_id)?;
+
+    print!("Accruals per day or month (daily/monthly, leave blank for daily): ");
+    io::stdout().flush().unwrap();
+    let mut granularity = String::new();
+    io::stdin().read_line(&mut granularity).unwrap();
+    let granularity = if granularity.trim().is_empty() { Granularity::Daily } else { granularity.parse()? };
+
+    let mut options = ReportOptions { granularity, ..ReportOptions::default() };
+    print!("Columns (base-rate, principal, interest, interest-excl-margin, commitment-fee, default-interest, balance, leave blank for all): ");
+    io::stdout().flush().unwrap();
+    let mut columns = String::new();
+    io::stdin().read_line(&mut columns).unwrap();
+    options.columns = report::parse_columns(&columns)?;
+
+    if granularity == Granularity::Daily {
+        print!("Monthly subtotals (y/n): ");
+        io::stdout().flush().unwrap();
+        let mut subtotals = String::new();
+        io::stdin().read_line(&mut subtotals).unwrap();
+        options.monthly_subtotals = subtotals.trim().eq_ignore_ascii_case("y");
+    }
+
+    print!("Rows per page (leave blank to show every row): ");
+    io::stdout().flush().unwrap();
+    let mut page_size = String::new();
+    io::stdin().read_line(&mut page_size).unwrap();
+    if !page_size.trim().is_empty() {
+        options.page_size = Some(page_size.trim().parse()?);
+    }
+
+    display::print_report(loan, &options)
+}
+
 fn add_loan(calculator: &mut LoanCalculator) -> Result<(), Error>{
     let loan = update_loan_parameters(Loan::new())?;
-    let loan_id = calculator.add_loan(loan);
-    let added_loan = calculator.loans.get_mut(&loan_id).unwrap();
+    let loan_id = calculator.add_loan(loan)?;
     println!("Loan added with ID: {}\n", loan_id);
-    added_loan.calculate_interest();
     Ok(())
 }
 
-fn print_interest_results(loan: Loan) {
-    println!("Loan Interest Calculation Results");
-    println!("--------------------------------");
-    // printing could be prettier but this is just a demo
-    // it is more important that the calculations are correct and we do not round too early
-    println!("{:#?}\n", loan);
+fn show_amortization_schedule(calculator: &mut LoanCalculator) -> Result<(), Error> {
+    print!("Enter the Loan ID: ");
+    io::stdout().flush().unwrap();
+    let mut loan_id_input = String::new();
+    io::stdin().read_line(&mut loan_id_input).unwrap();
+    let loan_id: u32 = loan_id_input.trim().parse()?;
+    let loan = calculator.get_loan(loan_id)?;
+
+    print!("Payment Frequency (monthly, quarterly, semi-annual, annual): ");
+    io::stdout().flush().unwrap();
+    let mut frequency = String::new();
+    io::stdin().read_line(&mut frequency).unwrap();
+
+    print!("Stub Period (short-front, long-front, short-back, long-back): ");
+    io::stdout().flush().unwrap();
+    let mut stub = String::new();
+    io::stdin().read_line(&mut stub).unwrap();
+
+    let calendar = calculator.market_data().calendars.joint(&loan.business_day_calendars)?;
+    let periods = schedule::amortization_schedule(loan, frequency.parse()?, stub.parse()?, &calendar);
+    display::print_amortization_schedule(loan, &periods);
+    Ok(())
+}
+
+fn load_fixings(calculator: &mut LoanCalculator) -> Result<(), Error> {
+    print!("Fixings CSV file (index,date,rate%): ");
+    io::stdout().flush().unwrap();
+    let mut path = String::new();
+    io::stdin().read_line(&mut path).unwrap();
+    let loaded = print_failed_loans(calculator.load_fixings(Path::new(path.trim()))?);
+    println!("Loaded {} fixings.", loaded);
+    for (index, count) in calculator.market_data().fixings.indices() {
+        println!("{}: {} fixings", index, count);
+    }
+    println!();
+    Ok(())
+}
+
+/// Reports loans that could not be recalculated with newly loaded market data, returning the number of entries loaded.
+fn print_failed_loans(load: MarketDataLoad) -> usize {
+    for (loan_id, e) in load.failed_loans.iter() {
+        println!("Loan with ID {} could not be recalculated: {}", loan_id, e);
+    }
+    load.loaded
+}
+
+fn load_fx_rates(calculator: &mut LoanCalculator) -> Result<(), Error> {
+    print!("FX rates CSV file (date,base,quote,rate): ");
+    io::stdout().flush().unwrap();
+    let mut path = String::new();
+    io::stdin().read_line(&mut path).unwrap();
+    let loaded = calculator.load_fx_rates(Path::new(path.trim()))?;
+    println!("Loaded {} FX rates.", loaded);
+    for (pair, count) in calculator.market_data().fx_rates.pairs() {
+        println!("{}: {} rates", pair, count);
+    }
+    println!();
+    Ok(())
+}
+
+fn show_portfolio_totals(calculator: &mut LoanCalculator) -> Result<(), Error> {
+    print!("Reporting Currency: ");
+    io::stdout().flush().unwrap();
+    let mut reporting_currency = String::new();
+    io::stdin().read_line(&mut reporting_currency).unwrap();
+    let reporting_currency: Currency = reporting_currency.parse()?;
+
+    print!("FX Rate Date (YYYY-MM-DD, leave blank for today): ");
+    io::stdout().flush().unwrap();
+    let mut date = String::new();
+    io::stdin().read_line(&mut date).unwrap();
+    let date = if date.trim().is_empty() {
+        Local::now().date_naive()
+    } else {
+        NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")?
+    };
+
+    let summary = portfolio::summarise(calculator.loans().values(), reporting_currency, date, &calculator.market_data().fx_rates)?;
+    display::print_portfolio(&summary);
+    Ok(())
+}
+
+/// Prompts for a value, returning None when left blank.
+fn read_optional<T: FromStr<Err = E>, E: Into<Error>>(prompt: &str) -> Result<Option<T>, Error> {
+    print!("{} (leave blank for any): ", prompt);
+    io::stdout().flush().unwrap();
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).unwrap();
+    if input.trim().is_empty() {
+        Ok(None)
+    } else {
+        input.trim().parse().map(Some).map_err(Into::into)
+    }
+}
+
+fn search_loans(calculator: &mut LoanCalculator) -> Result<(), Error> {
+    // divide by 100 to convert to %
+    let filter = LoanFilter {
+        currency: read_optional("Currency")?,
+        from: read_optional("Term overlaps from (YYYY-MM-DD)")?,
+        to: read_optional("Term overlaps to (YYYY-MM-DD)")?,
+        min_amount: read_optional("Minimum amount")?,
+        max_amount: read_optional("Maximum amount")?,
+        min_rate: read_optional::<Decimal, _>("Minimum all-in rate (%)")?.map(|rate| rate / dec!(100)),
+        max_rate: read_optional::<Decimal, _>("Maximum all-in rate (%)")?.map(|rate| rate / dec!(100)),
+        status: read_optional("Status (active, matured, archived)")?,
+    };
+    let sort = read_optional(&format!("Sort by ({})", SortKey::ALL.map(|key| key.to_string()).join(", ")))?.unwrap_or(SortKey::Id);
+    let descending = read_optional::<String, _>("Descending (y/n)")?.is_some_and(|answer| answer.eq_ignore_ascii_case("y"));
+
+    let results = query::query(calculator, &filter, sort, descending, Local::now().date_naive());
+    display::print_query_results(&results);
+    Ok(())
+}
+
+fn show_accrued_interest(calculator: &mut LoanCalculator) -> Result<(), Error> {
+    print!("Enter the Loan ID: ");
+    io::stdout().flush().unwrap();
+    let mut loan_id = String::new();
+    io::stdin().read_line(&mut loan_id).unwrap();
+    let loan = calculator.get_loan(loan_id.trim().parse()?)?;
+
+    print!("From (YYYY-MM-DD, blank for the start date): ");
+    io::stdout().flush().unwrap();
+    let mut from = String::new();
+    io::stdin().read_line(&mut from).unwrap();
+    let from = match from.trim() {
+        "" => None,
+        from => Some(NaiveDate::parse_from_str(from, "%Y-%m-%d")?),
+    };
+
+    print!("To (YYYY-MM-DD, blank for today): ");
+    io::stdout().flush().unwrap();
+    let mut to = String::new();
+    io::stdin().read_line(&mut to).unwrap();
+    let to = match to.trim() {
+        "" => Local::now().date_naive(),
+        to => NaiveDate::parse_from_str(to, "%Y-%m-%d")?,
+    };
+    let accrued = match from {
+        Some(from) => loan.accrued_between(from, to)?,
+        None => loan.accrued_to(to),
+    };
+    display::print_accrued(loan, from.unwrap_or(loan.start_date), to, &accrued);
+    Ok(())
+}
+
+fn payoff_quote(calculator: &mut LoanCalculator) -> Result<(), Error> {
+    print!("Enter the Loan ID: ");
+    io::stdout().flush().unwrap();
+    let mut loan_id = String::new();
+    io::stdin().read_line(&mut loan_id).unwrap();
+    let loan = calculator.get_loan(loan_id.trim().parse()?)?;
+
+    print!("Payoff Date (YYYY-MM-DD, blank for today): ");
+    io::stdout().flush().unwrap();
+    let mut payoff_date = String::new();
+    io::stdin().read_line(&mut payoff_date).unwrap();
+    let payoff_date = match payoff_date.trim() {
+        "" => Local::now().date_naive(),
+        payoff_date => NaiveDate::parse_from_str(payoff_date, "%Y-%m-%d")?,
+    };
+
+    print!("Break Cost (none, percentage=rate%, fee=amount, make-whole=reinvestment rate%): ");
+    io::stdout().flush().unwrap();
+    let mut break_cost = String::new();
+    io::stdin().read_line(&mut break_cost).unwrap();
+    let break_cost: BreakCost = break_cost.parse()?;
+
+    display::print_payoff_quote(loan, &loan.payoff_quote(payoff_date, break_cost, calculator.market_data())?);
+    Ok(())
+}
+
+fn import_loans(calculator: &mut LoanCalculator) -> Result<(), Error> {
+    print!("Loans CSV file (start_date,end_date,amount,currency,base_rate,margin[,day_count][,compounding]): ");
+    io::stdout().flush().unwrap();
+    let mut path = String::new();
+    io::stdin().read_line(&mut path).unwrap();
+    let report = import::import_csv(Path::new(path.trim()), calculator)?;
+    display::print_import_report(&report);
+    Ok(())
+}
+
+fn export_accruals(calculator: &mut LoanCalculator) -> Result<(), Error> {
+    print!("Loan ID (leave blank to export all loans): ");
+    io::stdout().flush().unwrap();
+    let mut loan_id = String::new();
+    io::stdin().read_line(&mut loan_id).unwrap();
+    let loan_id = loan_id.trim();
+
+    print!("Output file (.csv or .json): ");
+    io::stdout().flush().unwrap();
+    let mut path = String::new();
+    io::stdin().read_line(&mut path).unwrap();
+    let path = Path::new(path.trim());
+    let format = ExportFormat::from_path(path).ok_or(anyhow!("The output file must end in .csv or .json."))?;
+
+    if loan_id.is_empty() {
+        export::export_loans(calculator.loans().iter().map(|(loan_id, loan)| (*loan_id, loan)), format, path)?;
+    } else {
+        let loan_id = loan_id.parse::<u32>()?;
+        let loan = calculator.get_loan(loan_id)?;
+        export::export_loans(std::iter::once((loan_id, loan)), format, path)?;
+    }
+    println!("Daily accruals written to {}.", path.display());
+    println!();
+    Ok(())
+}
+
+fn load_calendar(calculator: &mut LoanCalculator) -> Result<(), Error> {
+    print!("Calendar Name (e.g. TARGET, GBLO, USNY): ");
+    io::stdout().flush().unwrap();
+    let mut name = String::new();
+    io::stdin().read_line(&mut name).unwrap();
+
+    print!("Holiday CSV file (date,description): ");
+    io::stdout().flush().unwrap();
+    let mut path = String::new();
+    io::stdin().read_line(&mut path).unwrap();
+    let loaded = print_failed_loans(calculator.load_calendar(&name, Path::new(path.trim()))?);
+    println!("Loaded {} holidays.", loaded);
+    for (name, count) in calculator.market_data().calendars.names() {
+        println!("{}: {} holidays", name, count);
+    }
+    println!();
+    Ok(())
 }
 
 fn update_loan_parameters(mut loan: Loan) -> Result<Loan, Error> {
@@ -212,41 +575,193 @@ fn update_loan_parameters(mut loan: Loan) -> Result<Loan, Error> {
     io::stdout().flush().unwrap();
     let mut start_date = String::new();
     io::stdin().read_line(&mut start_date).unwrap();
-    loan.start_date = NaiveDate::parse_from_str(&start_date.trim(), "%Y-%m-%d")?;
+    loan.start_date = NaiveDate::parse_from_str(start_date.trim(), "%Y-%m-%d")?;
 
     print!("End Date (YYYY-MM-DD): ");
     io::stdout().flush().unwrap();
     let mut end_date = String::new();
     io::stdin().read_line(&mut end_date).unwrap();
-    loan.end_date = NaiveDate::parse_from_str(&end_date.trim(), "%Y-%m-%d")?;
+    loan.end_date = NaiveDate::parse_from_str(end_date.trim(), "%Y-%m-%d")?;
 
     print!("Loan Amount: ");
     io::stdout().flush().unwrap();
     let mut loan_amount = String::new();
     io::stdin().read_line(&mut loan_amount).unwrap();
-    loan.loan_amount = loan_amount.trim().parse()?;
+    loan.loan_amount = loan_amount.trim().parse::<Decimal>()?;
 
     print!("Loan Currency: ");
     io::stdout().flush().unwrap();
     let mut loan_currency = String::new();
     io::stdin().read_line(&mut loan_currency).unwrap();
-    loan.loan_currency = loan_currency.trim().to_string();
+    loan.loan_currency = loan_currency.parse()?;
 
     print!("Base Interest Rate (%): ");
     io::stdout().flush().unwrap();
     let mut base_interest_rate = String::new();
     io::stdin().read_line(&mut base_interest_rate).unwrap();
     // divide by 100 to convert to %
-    loan.base_interest_rate = base_interest_rate.trim().parse::<f64>()?/100.0;
+    loan.base_interest_rate = base_interest_rate.trim().parse::<Decimal>()?/dec!(100);
+
+    print!("Base Rate Resets (YYYY-MM-DD=rate%, comma separated, blank for none): ");
+    io::stdout().flush().unwrap();
+    let mut base_rate_schedule = String::new();
+    io::stdin().read_line(&mut base_rate_schedule).unwrap();
+    // divide by 100 to convert to %
+    loan.base_rate_schedule = parse_dated_values(&base_rate_schedule)?
+        .into_iter()
+        .map(|(date, rate)| (date, rate / dec!(100)))
+        .collect();
+
+    print!("Benchmark Index (e.g. SOFR, blank to use the base rates entered above): ");
+    io::stdout().flush().unwrap();
+    let mut rate_index = String::new();
+    io::stdin().read_line(&mut rate_index).unwrap();
+    loan.rate_index = Some(rate_index.trim().to_uppercase()).filter(|index| !index.is_empty());
+
+    if loan.rate_index.is_some() {
+        print!("Missing Fixing Fallback (previous, error, fixed=<rate%>): ");
+        io::stdout().flush().unwrap();
+        let mut fixing_fallback = String::new();
+        io::stdin().read_line(&mut fixing_fallback).unwrap();
+        loan.fixing_fallback = fixing_fallback.parse()?;
+    }
 
     print!("Margin (%): ");
     io::stdout().flush().unwrap();
     let mut margin = String::new();
     io::stdin().read_line(&mut margin).unwrap();
     // divide by 100 to convert to %
-    loan.margin = margin.trim().parse::<f64>()?/100.0;
+    loan.margin = margin.trim().parse::<Decimal>()?/dec!(100);
+
+    print!(
+        "Day Count Convention (ACT/365F, ACT/360, ACT/365L, ACT/ACT ISDA, ACT/ACT ICMA, 30/360 US, 30E/360, 30E/360 ISDA, blank for the {} market default {}): ",
+        loan.loan_currency,
+        loan.loan_currency.default_day_count()
+    );
+    io::stdout().flush().unwrap();
+    let mut day_count_convention = String::new();
+    io::stdin().read_line(&mut day_count_convention).unwrap();
+    loan.day_count_convention = if day_count_convention.trim().is_empty() {
+        loan.loan_currency.default_day_count()
+    } else {
+        day_count_convention.parse()?
+    };
+
+    print!("Compounding (simple, daily, monthly, quarterly, annual, continuous, in-arrears): ");
+    io::stdout().flush().unwrap();
+    let mut compounding = String::new();
+    io::stdin().read_line(&mut compounding).unwrap();
+    loan.compounding = compounding.parse()?;
+
+    print!("Repayment Type (bullet, equal-principal, annuity, custom, balloon): ");
+    io::stdout().flush().unwrap();
+    let mut repayment_type = String::new();
+    io::stdin().read_line(&mut repayment_type).unwrap();
+    loan.repayment_schedule.repayment_type = repayment_type.parse()?;
+
+    match loan.repayment_schedule.repayment_type {
+        RepaymentType::Bullet => {}
+        RepaymentType::Custom => {
+            print!("Repayments (YYYY-MM-DD=amount, comma separated): ");
+            io::stdout().flush().unwrap();
+            let mut custom_repayments = String::new();
+            io::stdin().read_line(&mut custom_repayments).unwrap();
+            loan.repayment_schedule.custom_repayments = parse_dated_values(&custom_repayments)?;
+        }
+        RepaymentType::EqualPrincipal | RepaymentType::Annuity | RepaymentType::Balloon => {
+            print!("Repayment Frequency (monthly, quarterly, semi-annual, annual): ");
+            io::stdout().flush().unwrap();
+            let mut frequency = String::new();
+            io::stdin().read_line(&mut frequency).unwrap();
+            loan.repayment_schedule.frequency = frequency.parse()?;
+
+            print!("Stub Period (short-front, long-front, short-back, long-back): ");
+            io::stdout().flush().unwrap();
+            let mut stub = String::new();
+            io::stdin().read_line(&mut stub).unwrap();
+            loan.repayment_schedule.stub = stub.parse()?;
+
+            if loan.repayment_schedule.repayment_type == RepaymentType::Balloon {
+                print!("Balloon Amount: ");
+                io::stdout().flush().unwrap();
+                let mut balloon_amount = String::new();
+                io::stdin().read_line(&mut balloon_amount).unwrap();
+                loan.repayment_schedule.balloon_amount = balloon_amount.trim().parse::<Decimal>()?;
+            }
+        }
+    }
+
+    print!("Interest Payment Frequency (monthly, quarterly, semi-annual, annual, blank to pay with repayments or at maturity): ");
+    io::stdout().flush().unwrap();
+    let mut interest_frequency = String::new();
+    io::stdin().read_line(&mut interest_frequency).unwrap();
+    loan.interest_frequency = match interest_frequency.trim() {
+        "" => None,
+        interest_frequency => Some(interest_frequency.parse()?),
+    };
+
+    print!("Business Day Calendars (comma separated names, blank for weekends only): ");
+    io::stdout().flush().unwrap();
+    let mut business_day_calendars = String::new();
+    io::stdin().read_line(&mut business_day_calendars).unwrap();
+    loan.business_day_calendars = business_day_calendars
+        .split(',')
+        .map(|name| name.trim().to_uppercase())
+        .filter(|name| !name.is_empty())
+        .collect();
+
+    print!("Roll Convention (following, modified-following, preceding, modified-preceding, unadjusted): ");
+    io::stdout().flush().unwrap();
+    let mut roll_convention = String::new();
+    io::stdin().read_line(&mut roll_convention).unwrap();
+    loan.roll_convention = roll_convention.parse()?;
+
+    print!("Facility Limit (blank for a term loan): ");
+    io::stdout().flush().unwrap();
+    let mut facility_limit = String::new();
+    io::stdin().read_line(&mut facility_limit).unwrap();
+    loan.facility = None;
+    if !facility_limit.trim().is_empty() {
+        print!("Commitment Fee on Undrawn Amount (%): ");
+        io::stdout().flush().unwrap();
+        let mut commitment_fee_rate = String::new();
+        io::stdin().read_line(&mut commitment_fee_rate).unwrap();
+        loan.facility = Some(Facility {
+            limit: facility_limit.trim().parse::<Decimal>()?,
+            // divide by 100 to convert to %
+            commitment_fee_rate: commitment_fee_rate.trim().parse::<Decimal>()? / dec!(100),
+        });
+    }
+
+    print!("Drawdowns and Repayments (YYYY-MM-DD=+amount or YYYY-MM-DD=-amount, comma separated, blank for none): ");
+    io::stdout().flush().unwrap();
+    let mut ledger = String::new();
+    io::stdin().read_line(&mut ledger).unwrap();
+    loan.ledger = facility::parse_ledger(&ledger)?;
+
+    print!("Default Margin on Overdue Payments (%, blank for none): ");
+    io::stdout().flush().unwrap();
+    let mut default_margin = String::new();
+    io::stdin().read_line(&mut default_margin).unwrap();
+    // divide by 100 to convert to %
+    loan.default_margin = match default_margin.trim() {
+        "" => Decimal::ZERO,
+        default_margin => default_margin.parse::<Decimal>()? / dec!(100),
+    };
+
+    print!("Overdue Payments (YYYY-MM-DD=amount or YYYY-MM-DD=amount:YYYY-MM-DD once paid, comma separated, blank for none): ");
+    io::stdout().flush().unwrap();
+    let mut overdue_events = String::new();
+    io::stdin().read_line(&mut overdue_events).unwrap();
+    loan.overdue_events = overdue::parse_overdue(&overdue_events)?;
+
+    print!("Rounding Policy (bankers, half-up, truncate): ");
+    io::stdout().flush().unwrap();
+    let mut rounding_policy = String::new();
+    io::stdin().read_line(&mut rounding_policy).unwrap();
+    loan.rounding_policy = rounding_policy.parse()?;
 
     println!("Loan parameters updated successfully!\n");
 
     Ok(loan)
-}
\ No newline at end of file
+}
@@ -0,0 +1,68 @@
+use anyhow::{anyhow, Error};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A payment that was missed on its due date, charged default interest until it is paid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OverdueEvent {
+    pub due_date: NaiveDate,
+    pub amount: Decimal,
+    /// None while the amount is still unpaid.
+    pub paid_date: Option<NaiveDate>,
+}
+
+impl OverdueEvent {
+    /// Whether the amount is unpaid for the day starting on `date`, it is overdue from its due date up to the day it is paid.
+    pub fn is_overdue_on(&self, date: NaiveDate) -> bool {
+        self.due_date <= date && self.paid_date.is_none_or(|paid_date| date < paid_date)
+    }
+}
+
+/// Total amount overdue for the day starting on `date`.
+pub fn overdue_amount(events: &[OverdueEvent], date: NaiveDate) -> Decimal {
+    events.iter().filter(|event| event.is_overdue_on(date)).map(|event| event.amount).sum()
+}
+
+/// Parses comma separated `YYYY-MM-DD=amount` (unpaid) or `YYYY-MM-DD=amount:YYYY-MM-DD` (paid on the second date) entries.
+pub fn parse_overdue(input: &str) -> Result<Vec<OverdueEvent>, Error> {
+    let mut events = input
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<OverdueEvent>, Error>>()?;
+    events.sort_by_key(|event| event.due_date);
+    Ok(events)
+}
+
+impl FromStr for OverdueEvent {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (due_date, amount) = s
+            .trim()
+            .split_once('=')
+            .ok_or(anyhow!("Invalid overdue payment '{}'. Expected YYYY-MM-DD=amount or YYYY-MM-DD=amount:YYYY-MM-DD.", s.trim()))?;
+        let (amount, paid_date) = match amount.split_once(':') {
+            Some((amount, paid_date)) => (amount, Some(NaiveDate::parse_from_str(paid_date.trim(), "%Y-%m-%d")?)),
+            None => (amount, None),
+        };
+        Ok(OverdueEvent {
+            due_date: NaiveDate::parse_from_str(due_date.trim(), "%Y-%m-%d")?,
+            amount: amount.trim().parse()?,
+            paid_date,
+        })
+    }
+}
+
+impl fmt::Display for OverdueEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.paid_date {
+            Some(paid_date) => write!(f, "{}={}:{}", self.due_date, self.amount, paid_date),
+            None => write!(f, "{}={}", self.due_date, self.amount),
+        }
+    }
+}
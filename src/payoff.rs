@@ -0,0 +1,184 @@
+use anyhow::{anyhow, Error};
+use chrono::{Duration, NaiveDate};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::overdue;
+use crate::{AccruedInterest, Loan, MarketData};
+
+/// Charge for repaying a loan before maturity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum BreakCost {
+    None,
+    /// A percentage of the principal repaid.
+    Percentage(Decimal),
+    FixedFee(Decimal),
+    /// The interest the lender loses by reinvesting the principal at the given rate for the rest of the term,
+    /// discounted to the payoff date at that rate. Never negative.
+    MakeWhole(Decimal),
+}
+
+/// Settlement figure for repaying a loan in full on a given date.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct PayoffQuote {
+    pub payoff_date: NaiveDate,
+    pub outstanding_principal: Decimal,
+    /// Last interest payment date before the payoff date, or the start date, from which unpaid interest has accrued.
+    pub accrued_from: NaiveDate,
+    pub accrued: AccruedInterest,
+    /// Missed payments still unpaid on the payoff date.
+    pub overdue_amount: Decimal,
+    pub break_cost: Decimal,
+    pub total: Decimal,
+    /// Interest added to the total for each day the payoff date slips.
+    pub per_diem: Decimal,
+}
+
+impl Loan {
+    /// Quotes the amount needed to repay a calculated loan in full on `payoff_date`. Principal and interest
+    /// falling due on the payoff date are included, unpaid interest accrues from the last interest payment date.
+    pub fn payoff_quote(&self, payoff_date: NaiveDate, break_cost: BreakCost, market_data: &MarketData) -> Result<PayoffQuote, Error> {
+        if payoff_date < self.start_date || payoff_date > self.end_date {
+            return Err(anyhow!(
+                "Payoff date {} is outside the loan term {} to {}.",
+                payoff_date,
+                self.start_date,
+                self.end_date
+            ));
+        }
+        let calendar = market_data.calendars.joint(&self.business_day_calendars)?;
+        let accrued_from = self
+            .interest_payment_dates(&calendar)
+            .into_iter()
+            .rfind(|date| *date < payoff_date)
+            .unwrap_or(self.start_date);
+        let accrued = self.accrued_between(accrued_from, payoff_date)?;
+        // movements on the payoff date itself are settled by the payoff
+        let outstanding_principal =
+            self.loan_amount + self.principal_movements.range(..payoff_date).map(|(_, movement)| movement).sum::<Decimal>();
+
+        let accrual_periods = self.accrual_periods(&calendar);
+        let next_day = payoff_date + Duration::days(1);
+        // the rate of the day starting on the payoff date, or of the last day for a payoff at maturity
+        let base_rate = self
+            .daily_information
+            .range(..=next_day)
+            .next_back()
+            .map_or(self.base_interest_rate, |(_, daily)| daily.base_rate);
+        let per_diem = outstanding_principal
+            * (base_rate + self.margin)
+            * self.day_count_convention.fraction_over(payoff_date, next_day, &accrual_periods);
+
+        let break_cost = match break_cost {
+            BreakCost::None => Decimal::ZERO,
+            BreakCost::Percentage(rate) => outstanding_principal * rate,
+            BreakCost::FixedFee(fee) => fee,
+            BreakCost::MakeWhole(reinvestment_rate) => {
+                let lost_interest: Decimal = self
+                    .daily_information
+                    .range(next_day..)
+                    .map(|(date, daily)| {
+                        let day_fraction = self.day_count_convention.fraction_over(*date - Duration::days(1), *date, &accrual_periods);
+                        let discount_factor = Decimal::ONE
+                            + reinvestment_rate * self.day_count_convention.fraction_over(payoff_date, *date, &accrual_periods);
+                        daily.outstanding_principal * (daily.base_rate + self.margin - reinvestment_rate) * day_fraction / discount_factor
+                    })
+                    .sum();
+                lost_interest.max(Decimal::ZERO)
+            }
+        };
+
+        let overdue_amount = overdue::overdue_amount(&self.overdue_events, payoff_date);
+        Ok(PayoffQuote {
+            payoff_date,
+            outstanding_principal,
+            accrued_from,
+            accrued,
+            overdue_amount,
+            break_cost,
+            total: outstanding_principal
+                + accrued.interest
+                + accrued.commitment_fee
+                + overdue_amount
+                + accrued.default_interest
+                + break_cost,
+            per_diem,
+        })
+    }
+}
+
+impl FromStr for BreakCost {
+    type Err = Error;
+
+    /// Parses `none`, `percentage=<rate%>`, `fee=<amount>` or `make-whole=<reinvestment rate%>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalised = s.trim().to_lowercase();
+        let break_cost = match normalised.split_once('=') {
+            None if normalised == "none" => BreakCost::None,
+            // divide by 100 to convert to %
+            Some(("percentage", rate)) => BreakCost::Percentage(rate.trim().parse::<Decimal>()? / dec!(100)),
+            Some(("fee", fee)) => BreakCost::FixedFee(fee.trim().parse()?),
+            Some(("make-whole", rate)) => BreakCost::MakeWhole(rate.trim().parse::<Decimal>()? / dec!(100)),
+            _ => {
+                return Err(anyhow!(
+                    "Unknown break cost '{}'. Expected none, percentage=<rate>, fee=<amount> or make-whole=<rate>.",
+                    s.trim()
+                ))
+            }
+        };
+        match break_cost {
+            BreakCost::Percentage(value) | BreakCost::FixedFee(value) if value < Decimal::ZERO => {
+                Err(anyhow!("The break cost '{}' must not be negative.", s.trim()))
+            }
+            break_cost => Ok(break_cost),
+        }
+    }
+}
+
+impl fmt::Display for BreakCost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BreakCost::None => write!(f, "none"),
+            BreakCost::Percentage(rate) => write!(f, "percentage={}", (rate * dec!(100)).normalize()),
+            BreakCost::FixedFee(fee) => write!(f, "fee={}", fee.normalize()),
+            BreakCost::MakeWhole(rate) => write!(f, "make-whole={}", (rate * dec!(100)).normalize()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repayment::PaymentFrequency;
+    use crate::test_support::{calculated, date, loan};
+
+    fn bullet_loan() -> Loan {
+        let mut loan = loan(date(2024, 1, 1), date(2024, 12, 31));
+        loan.base_interest_rate = dec!(0.04);
+        loan.margin = Decimal::ZERO;
+        loan
+    }
+
+    #[test]
+    fn bullet_loans_accrue_from_the_start_date() {
+        let market_data = MarketData::default();
+        let loan = calculated(bullet_loan());
+        let payoff_date = date(2024, 7, 15);
+        let quote = loan.payoff_quote(payoff_date, BreakCost::None, &market_data).unwrap();
+        assert_eq!(quote.accrued_from, loan.start_date);
+        assert_eq!(quote.accrued.interest, loan.accrued_to(payoff_date).interest);
+    }
+
+    #[test]
+    fn interest_accrues_from_the_last_interest_payment_date() {
+        let market_data = MarketData::default();
+        let mut loan = bullet_loan();
+        loan.interest_frequency = Some(PaymentFrequency::Quarterly);
+        let loan = calculated(loan);
+        let quote = loan.payoff_quote(date(2024, 7, 15), BreakCost::None, &market_data).unwrap();
+        assert_eq!(quote.accrued_from, date(2024, 7, 1));
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::Error;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+use crate::currency::Currency;
+use crate::fx::FxRates;
+use crate::Loan;
+
+/// Loan count, principal and interest of a group of loans in a single currency.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Totals {
+    pub loans: usize,
+    /// Principal outstanding on the reporting date.
+    pub principal: Decimal,
+    pub interest: Decimal,
+}
+
+impl Totals {
+    fn add(&mut self, principal: Decimal, interest: Decimal) {
+        self.loans += 1;
+        self.principal += principal;
+        self.interest += interest;
+    }
+}
+
+/// Portfolio totals per loan currency and converted into a reporting currency.
+#[derive(Clone, Debug)]
+pub struct PortfolioSummary {
+    pub reporting_currency: Currency,
+    pub date: NaiveDate,
+    /// Totals in each loan currency with the FX rate used to convert them, keyed by currency code.
+    pub by_currency: BTreeMap<&'static str, (Currency, Totals, Decimal)>,
+    pub converted: Totals,
+}
+
+/// Totals the principal outstanding at the end of `date` and the total interest of `loans` per currency and converts
+/// them into `reporting_currency` at the most recent FX rates on or before `date`.
+pub fn summarise<'a>(
+    loans: impl Iterator<Item = &'a Loan>,
+    reporting_currency: Currency,
+    date: NaiveDate,
+    fx_rates: &FxRates,
+) -> Result<PortfolioSummary, Error> {
+    let mut by_currency: BTreeMap<&'static str, (Currency, Totals)> = BTreeMap::new();
+    for loan in loans {
+        let (_, totals) = by_currency
+            .entry(loan.loan_currency.code)
+            .or_insert((loan.loan_currency, Totals::default()));
+        totals.add(loan.outstanding_principal(date), loan.total_interest);
+    }
+
+    let mut converted = Totals::default();
+    let by_currency = by_currency
+        .into_iter()
+        .map(|(code, (currency, totals))| {
+            let rate = fx_rates.rate(currency, reporting_currency, date)?;
+            converted.loans += totals.loans;
+            converted.principal += totals.principal * rate;
+            converted.interest += totals.interest * rate;
+            Ok((code, (currency, totals, rate)))
+        })
+        .collect::<Result<BTreeMap<_, _>, Error>>()?;
+
+    Ok(PortfolioSummary {
+        reporting_currency,
+        date,
+        by_currency,
+        converted,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repayment::RepaymentType;
+    use crate::test_support::{calculated, date, loan};
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn principal_is_outstanding_on_the_reporting_date() {
+        let mut loan = loan(date(2024, 1, 1), date(2025, 1, 1));
+        loan.loan_amount = dec!(1200000);
+        loan.repayment_schedule.repayment_type = RepaymentType::EqualPrincipal;
+        let loan = calculated(loan);
+        let principal = |date| {
+            summarise(std::iter::once(&loan), Currency::USD, date, &FxRates::default())
+                .unwrap()
+                .converted
+                .principal
+        };
+
+        assert_eq!(principal(date(2023, 12, 31)), Decimal::ZERO);
+        // repaid on 1 February and 1 March
+        assert_eq!(principal(date(2024, 3, 15)), dec!(1000000));
+        assert_eq!(principal(date(2025, 1, 1)), Decimal::ZERO);
+    }
+}
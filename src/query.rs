@@ -0,0 +1,338 @@
+use anyhow::{anyhow, Error};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::currency::Currency;
+use crate::{Loan, LoanCalculator};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LoanStatus {
+    /// Not yet past its end date.
+    Active,
+    /// Past its end date.
+    Matured,
+    Archived,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    Id,
+    Status,
+    Currency,
+    Amount,
+    StartDate,
+    EndDate,
+    Rate,
+    Interest,
+    RemainingLife,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 9] = [
+        SortKey::Id,
+        SortKey::Status,
+        SortKey::Currency,
+        SortKey::Amount,
+        SortKey::StartDate,
+        SortKey::EndDate,
+        SortKey::Rate,
+        SortKey::Interest,
+        SortKey::RemainingLife,
+    ];
+}
+
+/// Criteria a loan must meet to be included, None matches every loan. Rates are compared against the all-in rate.
+#[derive(Clone, Debug, Default)]
+pub struct LoanFilter {
+    pub currency: Option<Currency>,
+    /// The loan's term must overlap the period from this date.
+    pub from: Option<NaiveDate>,
+    /// The loan's term must overlap the period up to this date.
+    pub to: Option<NaiveDate>,
+    pub min_amount: Option<Decimal>,
+    pub max_amount: Option<Decimal>,
+    pub min_rate: Option<Decimal>,
+    pub max_rate: Option<Decimal>,
+    pub status: Option<LoanStatus>,
+}
+
+impl LoanFilter {
+    fn matches(&self, loan: &Loan, status: LoanStatus) -> bool {
+        self.currency.is_none_or(|currency| loan.loan_currency == currency)
+            && self.from.is_none_or(|from| loan.end_date >= from)
+            && self.to.is_none_or(|to| loan.start_date <= to)
+            && self.min_amount.is_none_or(|min_amount| loan.loan_amount >= min_amount)
+            && self.max_amount.is_none_or(|max_amount| loan.loan_amount <= max_amount)
+            && self.min_rate.is_none_or(|min_rate| loan.all_in_rate() >= min_rate)
+            && self.max_rate.is_none_or(|max_rate| loan.all_in_rate() <= max_rate)
+            && self.status.is_none_or(|wanted| status == wanted)
+    }
+}
+
+/// A loan matched by a query with its status on the query date.
+#[derive(Clone, Copy, Debug)]
+pub struct QueryResult<'a> {
+    pub loan_id: u32,
+    pub loan: &'a Loan,
+    pub status: LoanStatus,
+    /// Years from the query date to the end date, zero once matured.
+    pub remaining_life: Decimal,
+    /// Principal drawn at the end of the query date, after drawdowns and repayments to date.
+    pub outstanding_principal: Decimal,
+}
+
+/// Statistics of the matched loans in one currency on the query date, rates and lives are weighted by outstanding principal.
+#[derive(Clone, Copy, Debug)]
+pub struct Statistics {
+    pub count: usize,
+    pub total_principal: Decimal,
+    pub weighted_average_rate: Decimal,
+    pub weighted_average_remaining_life: Decimal,
+}
+
+/// Finds the active and archived loans matching `filter`, with statuses and remaining lives measured on `as_of`.
+pub fn query<'a>(
+    calculator: &'a LoanCalculator,
+    filter: &LoanFilter,
+    sort: SortKey,
+    descending: bool,
+    as_of: NaiveDate,
+) -> Vec<QueryResult<'a>> {
+    let active = calculator.loans().iter().map(|(loan_id, loan)| {
+        let status = if loan.end_date > as_of { LoanStatus::Active } else { LoanStatus::Matured };
+        (*loan_id, loan, status)
+    });
+    let archived = calculator.archived_loans().iter().map(|(loan_id, loan)| (*loan_id, loan, LoanStatus::Archived));
+    let mut results: Vec<QueryResult> = active
+        .chain(archived)
+        .filter(|(_, loan, status)| filter.matches(loan, *status))
+        .map(|(loan_id, loan, status)| QueryResult {
+            loan_id,
+            loan,
+            status,
+            remaining_life: Decimal::from((loan.end_date - as_of).num_days().max(0)) / dec!(365),
+            outstanding_principal: loan.outstanding_principal(as_of),
+        })
+        .collect();
+
+    results.sort_by(|a, b| {
+        let ordering = match sort {
+            SortKey::Id => a.loan_id.cmp(&b.loan_id),
+            SortKey::Status => a.status.cmp(&b.status),
+            SortKey::Currency => a.loan.loan_currency.code.cmp(b.loan.loan_currency.code),
+            SortKey::Amount => a.loan.loan_amount.cmp(&b.loan.loan_amount),
+            SortKey::StartDate => a.loan.start_date.cmp(&b.loan.start_date),
+            SortKey::EndDate => a.loan.end_date.cmp(&b.loan.end_date),
+            SortKey::Rate => a.loan.all_in_rate().cmp(&b.loan.all_in_rate()),
+            SortKey::Interest => a.loan.total_interest.cmp(&b.loan.total_interest),
+            SortKey::RemainingLife => a.remaining_life.cmp(&b.remaining_life),
+        };
+        let ordering = if descending { ordering.reverse() } else { ordering };
+        // ties keep ID order in either direction
+        ordering.then(a.loan_id.cmp(&b.loan_id))
+    });
+    results
+}
+
+/// Statistics per currency, amounts in different currencies are never added together.
+pub fn statistics(results: &[QueryResult]) -> BTreeMap<&'static str, (Currency, Statistics)> {
+    let mut by_currency: BTreeMap<&'static str, (Currency, Vec<&QueryResult>)> = BTreeMap::new();
+    for result in results {
+        by_currency
+            .entry(result.loan.loan_currency.code)
+            .or_insert((result.loan.loan_currency, Vec::new()))
+            .1
+            .push(result);
+    }
+    by_currency
+        .into_iter()
+        .map(|(code, (currency, results))| {
+            let total_principal: Decimal = results.iter().map(|result| result.outstanding_principal).sum();
+            let weighted = |value: fn(&QueryResult) -> Decimal| {
+                if total_principal.is_zero() {
+                    Decimal::ZERO
+                } else {
+                    results.iter().map(|result| value(result) * result.outstanding_principal).sum::<Decimal>() / total_principal
+                }
+            };
+            let statistics = Statistics {
+                count: results.len(),
+                total_principal,
+                weighted_average_rate: weighted(|result| result.loan.all_in_rate()),
+                weighted_average_remaining_life: weighted(|result| result.remaining_life),
+            };
+            (code, (currency, statistics))
+        })
+        .collect()
+}
+
+impl FromStr for LoanStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "active" => Ok(LoanStatus::Active),
+            "matured" => Ok(LoanStatus::Matured),
+            "archived" => Ok(LoanStatus::Archived),
+            other => Err(anyhow!("Unknown status '{}'. Expected active, matured or archived.", other)),
+        }
+    }
+}
+
+impl fmt::Display for LoanStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LoanStatus::Active => "active",
+            LoanStatus::Matured => "matured",
+            LoanStatus::Archived => "archived",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalised = s.trim().to_lowercase();
+        SortKey::ALL
+            .iter()
+            .find(|key| key.to_string() == normalised)
+            .copied()
+            .ok_or(anyhow!(
+                "Unknown sort field '{}'. Expected one of {}.",
+                s.trim(),
+                SortKey::ALL.map(|key| key.to_string()).join(", ")
+            ))
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SortKey::Id => "id",
+            SortKey::Status => "status",
+            SortKey::Currency => "currency",
+            SortKey::Amount => "amount",
+            SortKey::StartDate => "start-date",
+            SortKey::EndDate => "end-date",
+            SortKey::Rate => "rate",
+            SortKey::Interest => "interest",
+            SortKey::RemainingLife => "remaining-life",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repayment::{PaymentFrequency, RepaymentType};
+    use crate::test_support::{date, loan};
+
+    /// Three USD loans and an archived EUR loan:
+    /// 1. 1,000,000 at 6% to 2026, a bullet
+    /// 2. 1,000,000 at 8% through 2024, repaid quarterly in equal principal amounts
+    /// 3. 3,000,000 at 6% through 2023, matured
+    /// 4. 2,000,000 EUR at 6% to 2026, archived
+    fn book() -> LoanCalculator {
+        let mut calculator = LoanCalculator::new();
+        calculator.add_loan(loan(date(2024, 1, 1), date(2026, 1, 1))).unwrap();
+        let mut amortizing = loan(date(2024, 1, 1), date(2025, 1, 1));
+        amortizing.margin = dec!(0.03);
+        amortizing.repayment_schedule.repayment_type = RepaymentType::EqualPrincipal;
+        amortizing.repayment_schedule.frequency = PaymentFrequency::Quarterly;
+        calculator.add_loan(amortizing).unwrap();
+        let mut matured = loan(date(2023, 1, 1), date(2024, 1, 1));
+        matured.loan_amount = dec!(3000000);
+        calculator.add_loan(matured).unwrap();
+        let mut archived = loan(date(2024, 1, 1), date(2026, 1, 1));
+        archived.loan_amount = dec!(2000000);
+        archived.loan_currency = "EUR".parse().unwrap();
+        let loan_id = calculator.add_loan(archived).unwrap();
+        calculator.archive_loan(loan_id).unwrap();
+        calculator
+    }
+
+    fn loan_ids(calculator: &LoanCalculator, filter: &LoanFilter, sort: SortKey, descending: bool) -> Vec<u32> {
+        query(calculator, filter, sort, descending, date(2024, 7, 1))
+            .iter()
+            .map(|result| result.loan_id)
+            .collect()
+    }
+
+    #[test]
+    fn statuses_and_sort_keys_are_parsed_case_insensitively() {
+        assert_eq!(" Matured ".parse::<LoanStatus>().unwrap(), LoanStatus::Matured);
+        assert_eq!("ARCHIVED".parse::<LoanStatus>().unwrap(), LoanStatus::Archived);
+        assert!("closed".parse::<LoanStatus>().is_err());
+        assert_eq!("Start-Date".parse::<SortKey>().unwrap(), SortKey::StartDate);
+        assert_eq!("remaining-life".parse::<SortKey>().unwrap(), SortKey::RemainingLife);
+        assert!("maturity".parse::<SortKey>().is_err());
+        for key in SortKey::ALL {
+            assert_eq!(key.to_string().parse::<SortKey>().unwrap(), key);
+        }
+    }
+
+    #[test]
+    fn filters_combine() {
+        let calculator = book();
+        let all = LoanFilter::default();
+        assert_eq!(loan_ids(&calculator, &all, SortKey::Id, false), [1, 2, 3, 4]);
+
+        let usd = LoanFilter {
+            currency: Some(Currency::USD),
+            ..LoanFilter::default()
+        };
+        assert_eq!(loan_ids(&calculator, &usd, SortKey::Id, false), [1, 2, 3]);
+
+        let matured = LoanFilter {
+            status: Some(LoanStatus::Matured),
+            ..LoanFilter::default()
+        };
+        assert_eq!(loan_ids(&calculator, &matured, SortKey::Id, false), [3]);
+
+        // loans running in 2025 of at most 1,000,000 with an all-in rate of 7% or more
+        let filter = LoanFilter {
+            from: Some(date(2025, 1, 1)),
+            to: Some(date(2025, 12, 31)),
+            max_amount: Some(dec!(1000000)),
+            min_rate: Some(dec!(0.07)),
+            ..LoanFilter::default()
+        };
+        assert_eq!(loan_ids(&calculator, &filter, SortKey::Id, false), [2]);
+    }
+
+    #[test]
+    fn sorting_breaks_ties_by_id() {
+        let calculator = book();
+        let all = LoanFilter::default();
+        assert_eq!(loan_ids(&calculator, &all, SortKey::Amount, false), [1, 2, 4, 3]);
+        assert_eq!(loan_ids(&calculator, &all, SortKey::Amount, true), [3, 4, 1, 2]);
+        assert_eq!(loan_ids(&calculator, &all, SortKey::Status, false), [1, 2, 3, 4]);
+        assert_eq!(loan_ids(&calculator, &all, SortKey::Rate, true), [2, 1, 3, 4]);
+        assert_eq!(loan_ids(&calculator, &all, SortKey::EndDate, false), [3, 2, 1, 4]);
+    }
+
+    #[test]
+    fn statistics_are_weighted_by_outstanding_principal() {
+        let calculator = book();
+        let results = query(&calculator, &LoanFilter::default(), SortKey::Id, false, date(2024, 7, 1));
+        let statistics = statistics(&results);
+
+        // loan 2 has repaid half its principal by 1 July and loan 3 has been repaid in full
+        let (_, usd) = statistics["USD"];
+        assert_eq!(usd.count, 3);
+        assert_eq!(usd.total_principal, dec!(1500000));
+        assert_eq!(usd.weighted_average_rate.round_dp(6), dec!(0.066667));
+        // (1,000,000 * 549 + 500,000 * 184) / 365 / 1,500,000
+        assert_eq!(usd.weighted_average_remaining_life.round_dp(6), dec!(1.170776));
+
+        let (_, eur) = statistics["EUR"];
+        assert_eq!((eur.count, eur.total_principal), (1, dec!(2000000)));
+    }
+}
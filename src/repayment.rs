@@ -0,0 +1,246 @@
+use anyhow::{anyhow, Error};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::calendar::{HolidayCalendar, RollConvention};
+use crate::day_count::{AccrualPeriod, DayCountConvention};
+use crate::schedule::{self, StubType};
+
+/// How the principal of a loan is repaid over its life.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RepaymentType {
+    /// All principal is repaid at maturity.
+    Bullet,
+    /// The same amount of principal is repaid on every payment date.
+    EqualPrincipal,
+    /// Level payments of principal plus interest, the principal part grows as the balance falls.
+    Annuity,
+    /// Repayments on user supplied dates, anything left is repaid at maturity.
+    Custom,
+    /// Level payments like an annuity but leaving a balloon amount to be repaid at maturity.
+    Balloon,
+}
+
+/// How often scheduled payments fall due.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaymentFrequency {
+    Monthly,
+    Quarterly,
+    SemiAnnual,
+    Annual,
+}
+
+impl PaymentFrequency {
+    pub fn months(&self) -> u32 {
+        match self {
+            PaymentFrequency::Monthly => 1,
+            PaymentFrequency::Quarterly => 3,
+            PaymentFrequency::SemiAnnual => 6,
+            PaymentFrequency::Annual => 12,
+        }
+    }
+
+    pub fn periods_per_year(&self) -> u32 {
+        12 / self.months()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RepaymentSchedule {
+    pub repayment_type: RepaymentType,
+    pub frequency: PaymentFrequency,
+    pub stub: StubType,
+    // only used by RepaymentType::Custom
+    pub custom_repayments: BTreeMap<NaiveDate, Decimal>,
+    // only used by RepaymentType::Balloon
+    pub balloon_amount: Decimal,
+}
+
+impl RepaymentSchedule {
+    pub fn bullet() -> Self {
+        RepaymentSchedule {
+            repayment_type: RepaymentType::Bullet,
+            frequency: PaymentFrequency::Monthly,
+            stub: StubType::ShortBack,
+            custom_repayments: BTreeMap::new(),
+            balloon_amount: Decimal::ZERO,
+        }
+    }
+
+    /// Returns the principal repaid on each date of the loan term made up of `accrual_periods`, the amounts always add
+    /// up to `principal`. `annual_rate` is the all-in rate used to size level payments for annuity and balloon loans,
+    /// with each period's interest measured on `day_count_convention` so the payment stays level when periods differ in length.
+    /// Scheduled payment dates are rolled onto business days of `calendar`, custom repayment dates are used as entered.
+    pub fn principal_repayments(
+        &self,
+        principal: Decimal,
+        annual_rate: Decimal,
+        day_count_convention: DayCountConvention,
+        accrual_periods: &[AccrualPeriod],
+        calendar: &HolidayCalendar,
+        roll_convention: RollConvention,
+    ) -> Result<BTreeMap<NaiveDate, Decimal>, Error> {
+        let mut repayments = BTreeMap::new();
+        let (Some(first), Some(last)) = (accrual_periods.first(), accrual_periods.last()) else {
+            return Ok(repayments);
+        };
+        let (start_date, end_date) = (first.start, last.end);
+        if end_date <= start_date {
+            return Ok(repayments);
+        }
+        let payment_dates =
+            schedule::adjusted_payment_dates(start_date, end_date, self.frequency, self.stub, calendar, roll_convention);
+        let periods = payment_dates.len() as u64;
+        match self.repayment_type {
+            RepaymentType::Bullet => {
+                repayments.insert(end_date, principal);
+            }
+            RepaymentType::EqualPrincipal => {
+                let instalment = principal / Decimal::from(periods);
+                for date in payment_dates.iter() {
+                    repayments.insert(*date, instalment);
+                }
+            }
+            RepaymentType::Annuity | RepaymentType::Balloon => {
+                let balloon = if self.repayment_type == RepaymentType::Balloon {
+                    self.balloon_amount
+                } else {
+                    Decimal::ZERO
+                };
+                if balloon > principal {
+                    return Err(anyhow!("Balloon amount {} is larger than the principal {}.", balloon, principal));
+                }
+                // the loan accrues interest on the balance at the start of each period
+                let periodic_rates: Vec<Decimal> = std::iter::once(start_date)
+                    .chain(payment_dates.iter().copied())
+                    .zip(payment_dates.iter())
+                    .map(|(from, to)| annual_rate * day_count_convention.fraction_over(from, *to, accrual_periods))
+                    .collect();
+                let payment = level_payment(principal, balloon, &periodic_rates);
+                let mut balance = principal;
+                for (date, periodic_rate) in payment_dates.iter().zip(periodic_rates) {
+                    let principal_part = (payment - balance * periodic_rate).min(balance - balloon);
+                    repayments.insert(*date, principal_part);
+                    balance -= principal_part;
+                }
+            }
+            RepaymentType::Custom => {
+                let scheduled: Decimal = self.custom_repayments.values().sum();
+                if scheduled > principal {
+                    return Err(anyhow!("Custom repayments total {} which is more than the principal {}.", scheduled, principal));
+                }
+                if let Some(date) = self.custom_repayments.keys().find(|date| **date <= start_date || **date > end_date) {
+                    return Err(anyhow!("Custom repayment on {} is outside the loan term.", date));
+                }
+                repayments = self.custom_repayments.clone();
+            }
+        }
+        // whatever has not been repaid by the final payment date (e.g. a balloon) is repaid at maturity
+        let repaid: Decimal = repayments.values().sum();
+        if repaid < principal {
+            *repayments.entry(end_date).or_insert(Decimal::ZERO) += principal - repaid;
+        }
+        Ok(repayments)
+    }
+}
+
+/// Level payment that amortises `principal` down to `balloon` over periods charging `periodic_rates` of interest.
+fn level_payment(principal: Decimal, balloon: Decimal, periodic_rates: &[Decimal]) -> Decimal {
+    // growth of the principal over the whole term, and of each payment from its date to the end
+    let (mut growth, mut payments_growth) = (Decimal::ONE, Decimal::ZERO);
+    for periodic_rate in periodic_rates.iter().rev() {
+        payments_growth += growth;
+        growth *= Decimal::ONE + periodic_rate;
+    }
+    (principal * growth - balloon) / payments_growth
+}
+
+impl FromStr for RepaymentType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "bullet" => Ok(RepaymentType::Bullet),
+            "equal-principal" => Ok(RepaymentType::EqualPrincipal),
+            "annuity" => Ok(RepaymentType::Annuity),
+            "custom" => Ok(RepaymentType::Custom),
+            "balloon" => Ok(RepaymentType::Balloon),
+            other => Err(anyhow!(
+                "Unknown repayment type '{}'. Expected bullet, equal-principal, annuity, custom or balloon.",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for RepaymentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RepaymentType::Bullet => "bullet",
+            RepaymentType::EqualPrincipal => "equal-principal",
+            RepaymentType::Annuity => "annuity",
+            RepaymentType::Custom => "custom",
+            RepaymentType::Balloon => "balloon",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for PaymentFrequency {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "monthly" => Ok(PaymentFrequency::Monthly),
+            "quarterly" => Ok(PaymentFrequency::Quarterly),
+            "semi-annual" => Ok(PaymentFrequency::SemiAnnual),
+            "annual" => Ok(PaymentFrequency::Annual),
+            other => Err(anyhow!(
+                "Unknown payment frequency '{}'. Expected monthly, quarterly, semi-annual or annual.",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PaymentFrequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PaymentFrequency::Monthly => "monthly",
+            PaymentFrequency::Quarterly => "quarterly",
+            PaymentFrequency::SemiAnnual => "semi-annual",
+            PaymentFrequency::Annual => "annual",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{calculated, date, loan};
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn annuity_payments_are_level_on_actual_day_counts() {
+        let mut loan = loan(date(2024, 1, 1), date(2025, 1, 1));
+        loan.base_interest_rate = dec!(0.06);
+        loan.margin = Decimal::ZERO;
+        loan.day_count_convention = DayCountConvention::Act365Fixed;
+        loan.repayment_schedule.repayment_type = RepaymentType::Annuity;
+        let loan = calculated(loan);
+
+        let calendar = HolidayCalendar::default();
+        let periods = schedule::amortization_schedule(&loan, PaymentFrequency::Monthly, StubType::ShortBack, &calendar);
+        assert_eq!(periods.len(), 12);
+        let payment = periods[0].total_payment;
+        for period in periods.iter() {
+            assert_eq!(period.total_payment.round_dp(6), payment.round_dp(6), "payment on {}", period.payment_date);
+        }
+        assert_eq!(periods.last().unwrap().closing_balance.round_dp(6), Decimal::ZERO);
+    }
+}
@@ -0,0 +1,332 @@
+use anyhow::{anyhow, Error};
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::currency::Currency;
+use crate::{DailyInformation, Loan, RoundingPolicy};
+
+/// Whether the accrual table has a row per day or per calendar month.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Granularity {
+    #[default]
+    Daily,
+    Monthly,
+}
+
+/// Optional columns of the accrual table, the date and day columns are always shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportColumn {
+    BaseRate,
+    Principal,
+    Interest,
+    InterestExclMargin,
+    CommitmentFee,
+    DefaultInterest,
+    Balance,
+}
+
+impl ReportColumn {
+    pub const ALL: [ReportColumn; 7] = [
+        ReportColumn::BaseRate,
+        ReportColumn::Principal,
+        ReportColumn::Interest,
+        ReportColumn::InterestExclMargin,
+        ReportColumn::CommitmentFee,
+        ReportColumn::DefaultInterest,
+        ReportColumn::Balance,
+    ];
+
+    fn heading(&self) -> &'static str {
+        match self {
+            ReportColumn::BaseRate => "Base Rate %",
+            ReportColumn::Principal => "Principal",
+            ReportColumn::Interest => "Interest",
+            ReportColumn::InterestExclMargin => "Interest excl. Margin",
+            ReportColumn::CommitmentFee => "Commitment Fee",
+            ReportColumn::DefaultInterest => "Default Interest",
+            ReportColumn::Balance => "Balance",
+        }
+    }
+
+    /// Columns that are summed in monthly rows and subtotals, the others show the month end value.
+    fn is_summed(&self) -> bool {
+        matches!(
+            self,
+            ReportColumn::Interest
+                | ReportColumn::InterestExclMargin
+                | ReportColumn::CommitmentFee
+                | ReportColumn::DefaultInterest
+        )
+    }
+
+    fn value(&self, daily: &DailyInformation) -> Decimal {
+        match self {
+            ReportColumn::BaseRate => daily.base_rate,
+            ReportColumn::Principal => daily.outstanding_principal,
+            ReportColumn::Interest => daily.day_interest,
+            ReportColumn::InterestExclMargin => daily.day_interest_no_margin,
+            ReportColumn::CommitmentFee => daily.commitment_fee,
+            ReportColumn::DefaultInterest => daily.default_interest,
+            ReportColumn::Balance => daily.capitalised_balance,
+        }
+    }
+}
+
+/// How the accrual table of a loan report is laid out.
+#[derive(Clone, Debug)]
+pub struct ReportOptions {
+    pub granularity: Granularity,
+    pub columns: Vec<ReportColumn>,
+    /// Number of table rows per page, None prints the whole table at once.
+    pub page_size: Option<usize>,
+    /// Prints only this page (starting at 1) instead of paging through the table interactively.
+    pub page: Option<usize>,
+    /// Adds a subtotal row after the last day of each month in daily tables.
+    pub monthly_subtotals: bool,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        ReportOptions {
+            granularity: Granularity::Daily,
+            columns: ReportColumn::ALL.to_vec(),
+            page_size: None,
+            page: None,
+            monthly_subtotals: false,
+        }
+    }
+}
+
+/// Formats an amount in the currency's minor units with thousands separators and the currency code,
+/// e.g. `USD 1,234,567.89` or `JPY 1,234,568`.
+pub fn format_amount(value: Decimal, currency: Currency, policy: RoundingPolicy) -> String {
+    format!("{} {}", currency, group_thousands(value, currency.minor_units, policy))
+}
+
+pub fn group_thousands(value: Decimal, dp: u32, policy: RoundingPolicy) -> String {
+    let formatted = format!("{:.*}", dp as usize, policy.round(value, dp));
+    let (sign, unsigned) = match formatted.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", formatted.as_str()),
+    };
+    let (whole, fraction) = unsigned.split_once('.').map_or((unsigned, None), |(whole, fraction)| (whole, Some(fraction)));
+    let mut grouped = String::new();
+    for (index, digit) in whole.chars().enumerate() {
+        if index > 0 && (whole.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    match fraction {
+        Some(fraction) => format!("{}{}.{}", sign, grouped, fraction),
+        None => format!("{}{}", sign, grouped),
+    }
+}
+
+pub fn format_rate(rate: Decimal) -> String {
+    format!("{}%", (rate * dec!(100)).round_dp(6).normalize())
+}
+
+/// Loan summary lines: dates, amount, all-in rate and the total interest split into base and margin.
+pub fn summary(loan: &Loan) -> Vec<String> {
+    let policy = loan.rounding_policy;
+    let currency = loan.loan_currency;
+    let average_base_rate = loan.average_base_rate();
+    let base_interest: Decimal = loan.daily_information.values().map(|daily| daily.day_interest_no_margin).sum();
+    let margin_interest = loan.total_interest - base_interest;
+
+    let mut lines = vec![
+        format!("Term:            {} to {} ({} days)", loan.start_date, loan.end_date, (loan.end_date - loan.start_date).num_days()),
+        format!("Amount:          {}", format_amount(loan.loan_amount, currency, policy)),
+        format!(
+            "All-in Rate:     {} ({}base {} + margin {})",
+            format_rate(loan.all_in_rate()),
+            if loan.rate_index.is_some() || !loan.base_rate_schedule.is_empty() { "average " } else { "" },
+            format_rate(average_base_rate),
+            format_rate(loan.margin)
+        ),
+        format!("Conventions:     {}, {} compounding, {} rounding", loan.day_count_convention, loan.compounding, policy),
+        format!("Total Interest:  {}", format_amount(loan.total_interest, currency, policy)),
+        format!("  Base:          {}", format_amount(base_interest, currency, policy)),
+        format!("  Margin:        {}", format_amount(margin_interest, currency, policy)),
+    ];
+    if loan.facility.is_some() {
+        lines.push(format!("Commitment Fee:  {}", format_amount(loan.total_commitment_fee, currency, policy)));
+    }
+    if !loan.overdue_events.is_empty() {
+        lines.push(format!(
+            "Default Int.:    {} (default margin {})",
+            format_amount(loan.total_default_interest, currency, policy),
+            format_rate(loan.default_margin)
+        ));
+    }
+    lines
+}
+
+/// Accrual table rows, the first row holds the column headings.
+pub fn accrual_table(loan: &Loan, options: &ReportOptions) -> Vec<String> {
+    let policy = loan.rounding_policy;
+    let day_heading = match options.granularity {
+        Granularity::Daily => "Day",
+        Granularity::Monthly => "Days",
+    };
+    let mut rows: Vec<Vec<String>> = vec![std::iter::once("Date".to_string())
+        .chain(std::iter::once(day_heading.to_string()))
+        .chain(options.columns.iter().map(|column| column.heading().to_string()))
+        .collect()];
+
+    let cell = |column: &ReportColumn, value: Decimal| match column {
+        ReportColumn::BaseRate => (value * dec!(100)).round_dp(6).normalize().to_string(),
+        _ => group_thousands(value, loan.loan_currency.minor_units, policy),
+    };
+
+    let months = months(loan);
+    for (month, days) in months.iter() {
+        let last_day = days.last().expect("months always have a day").1;
+        let month_values = options.columns.iter().map(|column| {
+            if column.is_summed() {
+                cell(column, days.iter().map(|(_, daily)| column.value(daily)).sum())
+            } else {
+                cell(column, column.value(last_day))
+            }
+        });
+        match options.granularity {
+            Granularity::Monthly => {
+                rows.push(
+                    std::iter::once(month.clone())
+                        .chain(std::iter::once(days.len().to_string()))
+                        .chain(month_values)
+                        .collect(),
+                );
+            }
+            Granularity::Daily => {
+                for (date, daily) in days.iter() {
+                    rows.push(
+                        std::iter::once(date.to_string())
+                            .chain(std::iter::once(daily.days_elapsed.to_string()))
+                            .chain(options.columns.iter().map(|column| cell(column, column.value(daily))))
+                            .collect(),
+                    );
+                }
+                if options.monthly_subtotals {
+                    let subtotal_values = options.columns.iter().map(|column| {
+                        if column.is_summed() {
+                            cell(column, days.iter().map(|(_, daily)| column.value(daily)).sum())
+                        } else {
+                            String::new()
+                        }
+                    });
+                    rows.push(
+                        std::iter::once(format!("{} total", month))
+                            .chain(std::iter::once(days.len().to_string()))
+                            .chain(subtotal_values)
+                            .collect(),
+                    );
+                }
+            }
+        }
+    }
+
+    align(&rows)
+}
+
+/// Lays out table rows with the first column left aligned and the others right aligned, each column
+/// as wide as its widest cell.
+pub fn align(rows: &[Vec<String>]) -> Vec<String> {
+    let widths: Vec<usize> = (0..rows.first().map_or(0, Vec::len))
+        .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or(0))
+        .collect();
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .zip(widths.iter())
+                .enumerate()
+                .map(|(index, (value, width))| {
+                    if index == 0 {
+                        format!("{:<width$}", value, width = width)
+                    } else {
+                        format!("{:>width$}", value, width = width)
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+/// Groups the daily information by calendar month, labelled `YYYY-MM`.
+fn months(loan: &Loan) -> Vec<(String, Vec<(NaiveDate, &DailyInformation)>)> {
+    let mut months: Vec<(String, Vec<(NaiveDate, &DailyInformation)>)> = Vec::new();
+    for (date, daily) in loan.daily_information.iter() {
+        let month = format!("{}-{:02}", date.year(), date.month());
+        match months.last_mut() {
+            Some((last_month, days)) if *last_month == month => days.push((*date, daily)),
+            _ => months.push((month, vec![(*date, daily)])),
+        }
+    }
+    months
+}
+
+impl FromStr for Granularity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "daily" => Ok(Granularity::Daily),
+            "monthly" => Ok(Granularity::Monthly),
+            other => Err(anyhow!("Unknown granularity '{}'. Expected daily or monthly.", other)),
+        }
+    }
+}
+
+impl FromStr for ReportColumn {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalised = s.trim().to_lowercase();
+        ReportColumn::ALL
+            .iter()
+            .find(|column| column.to_string() == normalised)
+            .copied()
+            .ok_or(anyhow!(
+                "Unknown column '{}'. Expected one of {}.",
+                s.trim(),
+                ReportColumn::ALL.map(|column| column.to_string()).join(", ")
+            ))
+    }
+}
+
+impl fmt::Display for ReportColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ReportColumn::BaseRate => "base-rate",
+            ReportColumn::Principal => "principal",
+            ReportColumn::Interest => "interest",
+            ReportColumn::InterestExclMargin => "interest-excl-margin",
+            ReportColumn::CommitmentFee => "commitment-fee",
+            ReportColumn::DefaultInterest => "default-interest",
+            ReportColumn::Balance => "balance",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Parses comma separated column names, an empty list selects every column.
+pub fn parse_columns(input: &str) -> Result<Vec<ReportColumn>, Error> {
+    let columns = input
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<ReportColumn>, Error>>()?;
+    if columns.is_empty() {
+        Ok(ReportColumn::ALL.to_vec())
+    } else {
+        Ok(columns)
+    }
+}
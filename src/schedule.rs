@@ -0,0 +1,234 @@
+use anyhow::{anyhow, Error};
+use chrono::{Months, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::calendar::{HolidayCalendar, RollConvention};
+use crate::repayment::PaymentFrequency;
+use crate::Loan;
+
+/// Where the irregular period goes when the loan term is not a whole number of payment periods,
+/// and whether it is kept as a short period or merged into its neighbour as a long one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StubType {
+    ShortFront,
+    LongFront,
+    ShortBack,
+    LongBack,
+}
+
+/// One payment period of an amortization schedule.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct SchedulePeriod {
+    pub payment_date: NaiveDate,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub opening_balance: Decimal,
+    pub interest_due: Decimal,
+    pub principal_due: Decimal,
+    pub total_payment: Decimal,
+    pub closing_balance: Decimal,
+}
+
+/// Returns the payment dates between `start_date` and `end_date`, the last one always being the end date.
+/// Back stubs roll regular periods forward from the start date, front stubs roll them back from the end date.
+pub fn payment_dates(start_date: NaiveDate, end_date: NaiveDate, frequency: PaymentFrequency, stub: StubType) -> Vec<NaiveDate> {
+    let months = frequency.months();
+    let mut dates = Vec::new();
+    if end_date <= start_date {
+        return dates;
+    }
+    match stub {
+        StubType::ShortBack | StubType::LongBack => {
+            let mut period = 1;
+            // dates are measured from the start date so month ends do not drift
+            let mut date = start_date.checked_add_months(Months::new(months)).unwrap();
+            while date < end_date {
+                dates.push(date);
+                period += 1;
+                date = start_date.checked_add_months(Months::new(months * period)).unwrap();
+            }
+            let has_stub = date != end_date;
+            if stub == StubType::LongBack && has_stub {
+                dates.pop();
+            }
+        }
+        StubType::ShortFront | StubType::LongFront => {
+            let mut period = 1;
+            let mut date = end_date.checked_sub_months(Months::new(months)).unwrap();
+            while date > start_date {
+                dates.push(date);
+                period += 1;
+                date = end_date.checked_sub_months(Months::new(months * period)).unwrap();
+            }
+            let has_stub = date != start_date;
+            if stub == StubType::LongFront && has_stub {
+                dates.pop();
+            }
+            dates.reverse();
+        }
+    }
+    dates.push(end_date);
+    dates
+}
+
+/// Returns the payment dates rolled onto business days. The end date is the loan's maturity and is kept as entered,
+/// adjusted dates that would fall on or outside the loan term are dropped.
+pub fn adjusted_payment_dates(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    frequency: PaymentFrequency,
+    stub: StubType,
+    calendar: &HolidayCalendar,
+    roll_convention: RollConvention,
+) -> Vec<NaiveDate> {
+    let mut dates: Vec<NaiveDate> = payment_dates(start_date, end_date, frequency, stub)
+        .into_iter()
+        .map(|date| if date == end_date { date } else { roll_convention.adjust(date, calendar) })
+        .filter(|date| *date > start_date && *date <= end_date)
+        .collect();
+    dates.dedup();
+    dates
+}
+
+/// Builds the period level schedule of a calculated loan from its daily accruals and principal movements.
+/// Interest accrued in a period is treated as due on the period's payment date, which is the business day
+/// the period end rolls to under the loan's roll convention.
+pub fn amortization_schedule(
+    loan: &Loan,
+    frequency: PaymentFrequency,
+    stub: StubType,
+    calendar: &HolidayCalendar,
+) -> Vec<SchedulePeriod> {
+    let mut periods = Vec::new();
+    let mut start_date = loan.start_date;
+    // drawdowns on the start date are part of the first period's opening balance
+    let mut balance = loan.loan_amount
+        + loan.principal_movements.get(&loan.start_date).copied().unwrap_or(Decimal::ZERO);
+    for end_date in adjusted_payment_dates(loan.start_date, loan.end_date, frequency, stub, calendar, loan.roll_convention) {
+        let interest_due: Decimal = loan
+            .daily_information
+            .range(start_date..=end_date)
+            .filter(|(date, _)| **date > start_date)
+            .map(|(_, daily)| daily.day_interest)
+            .sum();
+        let (mut principal_due, mut drawn) = (Decimal::ZERO, Decimal::ZERO);
+        for (_, movement) in loan.principal_movements.range(start_date..=end_date).filter(|(date, _)| **date > start_date) {
+            if movement.is_sign_negative() {
+                principal_due -= movement;
+            } else {
+                drawn += movement;
+            }
+        }
+        let closing_balance = balance + drawn - principal_due;
+        periods.push(SchedulePeriod {
+            payment_date: loan.roll_convention.adjust(end_date, calendar),
+            start_date,
+            end_date,
+            opening_balance: balance,
+            interest_due,
+            principal_due,
+            total_payment: interest_due + principal_due,
+            closing_balance,
+        });
+        balance = closing_balance;
+        start_date = end_date;
+    }
+    periods
+}
+
+impl FromStr for StubType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "short-front" => Ok(StubType::ShortFront),
+            "long-front" => Ok(StubType::LongFront),
+            "short-back" => Ok(StubType::ShortBack),
+            "long-back" => Ok(StubType::LongBack),
+            other => Err(anyhow!(
+                "Unknown stub type '{}'. Expected short-front, long-front, short-back or long-back.",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for StubType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            StubType::ShortFront => "short-front",
+            StubType::LongFront => "long-front",
+            StubType::ShortBack => "short-back",
+            StubType::LongBack => "long-back",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::date;
+
+    /// Quarterly dates over a term of ten and a half months, so every stub type has a stub to place.
+    fn quarterly(stub: StubType) -> Vec<NaiveDate> {
+        payment_dates(date(2024, 1, 15), date(2024, 12, 1), PaymentFrequency::Quarterly, stub)
+    }
+
+    #[test]
+    fn short_back_stub_ends_the_schedule() {
+        assert_eq!(
+            quarterly(StubType::ShortBack),
+            [date(2024, 4, 15), date(2024, 7, 15), date(2024, 10, 15), date(2024, 12, 1)]
+        );
+    }
+
+    #[test]
+    fn long_back_stub_merges_into_the_last_period() {
+        assert_eq!(quarterly(StubType::LongBack), [date(2024, 4, 15), date(2024, 7, 15), date(2024, 12, 1)]);
+    }
+
+    #[test]
+    fn short_front_stub_starts_the_schedule() {
+        assert_eq!(
+            quarterly(StubType::ShortFront),
+            [date(2024, 3, 1), date(2024, 6, 1), date(2024, 9, 1), date(2024, 12, 1)]
+        );
+    }
+
+    #[test]
+    fn long_front_stub_merges_into_the_first_period() {
+        assert_eq!(quarterly(StubType::LongFront), [date(2024, 6, 1), date(2024, 9, 1), date(2024, 12, 1)]);
+    }
+
+    #[test]
+    fn whole_periods_have_no_stub() {
+        let expected = [date(2024, 4, 15), date(2024, 7, 15), date(2024, 10, 15), date(2025, 1, 15)];
+        for stub in [StubType::ShortFront, StubType::LongFront, StubType::ShortBack, StubType::LongBack] {
+            assert_eq!(payment_dates(date(2024, 1, 15), date(2025, 1, 15), PaymentFrequency::Quarterly, stub), expected);
+        }
+    }
+
+    #[test]
+    fn back_stubs_keep_the_start_date_month_end() {
+        let dates = payment_dates(date(2024, 1, 31), date(2024, 4, 30), PaymentFrequency::Monthly, StubType::ShortBack);
+        assert_eq!(dates, [date(2024, 2, 29), date(2024, 3, 31), date(2024, 4, 30)]);
+    }
+
+    #[test]
+    fn adjusted_dates_roll_but_keep_the_maturity() {
+        // 1 June and 1 September 2024 are weekends, maturity on Sunday 1 December is kept as entered
+        let dates = adjusted_payment_dates(
+            date(2024, 1, 15),
+            date(2024, 12, 1),
+            PaymentFrequency::Quarterly,
+            StubType::ShortFront,
+            &HolidayCalendar::default(),
+            RollConvention::ModifiedFollowing,
+        );
+        assert_eq!(dates, [date(2024, 3, 1), date(2024, 6, 3), date(2024, 9, 2), date(2024, 12, 1)]);
+    }
+}
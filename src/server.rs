@@ -0,0 +1,234 @@
+use anyhow::{anyhow, Error};
+use serde_json::{json, Value};
+use std::fmt;
+use std::io::{self, Read};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::repayment::PaymentFrequency;
+use crate::schedule::{self, StubType};
+use crate::validation::ValidationErrors;
+use crate::{Loan, LoanCalculator, LoanNotFound};
+
+/// Port the server listens on when none is given.
+pub const DEFAULT_PORT: u16 = 8080;
+
+/// An error that is the client's fault, returned with its own status code.
+#[derive(Debug)]
+struct HttpError {
+    status: u16,
+    message: String,
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+fn bad_request(message: String) -> Error {
+    HttpError { status: 400, message }.into()
+}
+
+/// An HTTP server listening on `127.0.0.1`, bound before serving so the caller can report the address.
+pub struct LoanServer {
+    server: Server,
+    address: String,
+}
+
+impl LoanServer {
+    pub fn bind(port: u16) -> Result<Self, Error> {
+        let address = format!("127.0.0.1:{}", port);
+        let server = Server::http(&address).map_err(|e| anyhow!("Could not listen on {}: {}", address, e))?;
+        Ok(LoanServer { server, address })
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Serves the calculator's loans as JSON until the process is stopped.
+    /// Requests are handled one at a time so every change is saved before the next request is read.
+    ///
+    /// - `GET /loans` lists the active loans
+    /// - `POST /loans` adds a loan, `GET`, `PUT` and `DELETE /loans/{id}` read, replace and delete one
+    /// - `GET /loans/{id}/accruals` returns the daily accruals
+    /// - `GET /loans/{id}/schedule?frequency=quarterly&stub=short-front` returns the amortization schedule
+    /// - `POST /what-if` calculates a loan without adding it to the book
+    ///
+    /// Loans are sent in the loan book's format, with rates as fractions (0.05 for 5%); missing fields take the defaults
+    /// of `Loan::new()`, apart from the day count convention which defaults to the loan currency's market convention.
+    pub fn serve(&self, calculator: &mut LoanCalculator) {
+        for mut request in self.server.incoming_requests() {
+            let (status, body) = match handle(calculator, &mut request) {
+                Ok(response) => response,
+                Err(e) => (error_status(&e), error_body(&e)),
+            };
+            let response = Response::from_string(body.to_string())
+                .with_status_code(status)
+                .with_header(Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap());
+            // the client has gone away, there is no one left to tell
+            let _ = request.respond(response);
+        }
+    }
+}
+
+fn handle(calculator: &mut LoanCalculator, request: &mut Request) -> Result<(u16, Value), Error> {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let method = request.method().clone();
+
+    match (&method, segments.as_slice()) {
+        (Method::Get, ["loans"]) => {
+            let loans: Vec<Value> = calculator
+                .loans()
+                .iter()
+                .map(|(loan_id, loan)| {
+                    json!({
+                        "id": loan_id,
+                        "start_date": loan.start_date,
+                        "end_date": loan.end_date,
+                        "loan_amount": loan.loan_amount,
+                        "loan_currency": loan.loan_currency,
+                        "all_in_rate": loan.all_in_rate(),
+                        "total_interest": loan.total_interest,
+                    })
+                })
+                .collect();
+            Ok((200, Value::Array(loans)))
+        }
+        (Method::Post, ["loans"]) => {
+            let loan_id = calculator.add_loan(read_loan(request.as_reader())?)?;
+            Ok((201, json!({ "id": loan_id, "loan": calculator.get_loan(loan_id)? })))
+        }
+        (Method::Get, ["loans", loan_id]) => {
+            let loan_id = parse_loan_id(loan_id)?;
+            Ok((200, json!({ "id": loan_id, "loan": calculator.get_loan(loan_id)? })))
+        }
+        (Method::Put, ["loans", loan_id]) => {
+            let loan_id = parse_loan_id(loan_id)?;
+            calculator.get_loan(loan_id)?;
+            calculator.update_loan(loan_id, read_loan(request.as_reader())?)?;
+            Ok((200, json!({ "id": loan_id, "loan": calculator.get_loan(loan_id)? })))
+        }
+        (Method::Delete, ["loans", loan_id]) => {
+            let loan_id = parse_loan_id(loan_id)?;
+            let loan = calculator.delete_loan(loan_id)?;
+            Ok((200, json!({ "id": loan_id, "loan": loan })))
+        }
+        (Method::Get, ["loans", loan_id, "accruals"]) => {
+            let loan_id = parse_loan_id(loan_id)?;
+            let accruals: Vec<Value> = calculator
+                .get_loan(loan_id)?
+                .daily_information
+                .iter()
+                .map(|(date, daily)| {
+                    let mut accrual = json!(daily);
+                    accrual["date"] = json!(date);
+                    accrual
+                })
+                .collect();
+            Ok((200, Value::Array(accruals)))
+        }
+        (Method::Get, ["loans", loan_id, "schedule"]) => {
+            let loan_id = parse_loan_id(loan_id)?;
+            let mut frequency = PaymentFrequency::Monthly;
+            let mut stub = StubType::ShortBack;
+            for (name, value) in query.split('&').filter(|pair| !pair.is_empty()).map(|pair| pair.split_once('=').unwrap_or((pair, ""))) {
+                match name {
+                    "frequency" => frequency = value.parse().map_err(|e: Error| bad_request(e.to_string()))?,
+                    "stub" => stub = value.parse().map_err(|e: Error| bad_request(e.to_string()))?,
+                    other => return Err(bad_request(format!("Unknown query parameter '{}'. Expected frequency or stub.", other))),
+                }
+            }
+            let loan = calculator.get_loan(loan_id)?;
+            let calendar = calculator.market_data().calendars.joint(&loan.business_day_calendars)?;
+            Ok((200, json!(schedule::amortization_schedule(loan, frequency, stub, &calendar))))
+        }
+        (Method::Post, ["what-if"]) => {
+            let loan = calculator.calculate(read_loan(request.as_reader())?)?;
+            Ok((200, json!(loan)))
+        }
+        (_, ["loans"] | ["loans", _] | ["loans", _, "accruals" | "schedule"] | ["what-if"]) => Err(HttpError {
+            status: 405,
+            message: format!("{} is not allowed on {}.", method, path),
+        }
+        .into()),
+        _ => Err(HttpError {
+            status: 404,
+            message: format!("No such resource {}.", path),
+        }
+        .into()),
+    }
+}
+
+fn read_loan(body: &mut dyn Read) -> Result<Loan, Error> {
+    let mut text = String::new();
+    // a body that is not UTF-8 is the client's fault, not a failure to save the loan book
+    body.read_to_string(&mut text)
+        .map_err(|e| bad_request(format!("Could not read the request body: {}", e)))?;
+    parse_loan(&text).map_err(|e| bad_request(format!("Invalid loan: {}", e)))
+}
+
+fn parse_loan(body: &str) -> Result<Loan, serde_json::Error> {
+    let body: Value = serde_json::from_str(body)?;
+    let day_count_given = body.get("day_count_convention").is_some();
+    let mut loan: Loan = serde_json::from_value(body)?;
+    if !day_count_given {
+        loan.day_count_convention = loan.loan_currency.default_day_count();
+    }
+    Ok(loan)
+}
+
+fn parse_loan_id(loan_id: &str) -> Result<u32, Error> {
+    loan_id
+        .parse()
+        .map_err(|_| bad_request(format!("Invalid loan ID '{}'.", loan_id)))
+}
+
+fn error_status(error: &Error) -> u16 {
+    if let Some(error) = error.downcast_ref::<HttpError>() {
+        error.status
+    } else if error.is::<LoanNotFound>() {
+        404
+    } else if error.is::<ValidationErrors>() {
+        422
+    } else if error.chain().any(|cause| cause.is::<io::Error>()) {
+        // the loan book could not be saved
+        500
+    } else {
+        // the loan could not be calculated, e.g. a fixing is missing
+        422
+    }
+}
+
+fn error_body(error: &Error) -> Value {
+    match error.downcast_ref::<ValidationErrors>() {
+        Some(errors) => json!({ "error": error.to_string(), "fields": errors.0 }),
+        None => json!({ "error": error.to_string() }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day_count::DayCountConvention;
+
+    #[test]
+    fn day_count_defaults_to_the_currency_convention() {
+        let loan = parse_loan(r#"{"loan_currency":"GBP"}"#).unwrap();
+        assert_eq!(loan.day_count_convention, DayCountConvention::Act365Fixed);
+        let loan = parse_loan(r#"{"loan_currency":"GBP","day_count_convention":"Act360"}"#).unwrap();
+        assert_eq!(loan.day_count_convention, DayCountConvention::Act360);
+    }
+
+    #[test]
+    fn bodies_that_are_not_utf8_are_bad_requests() {
+        let error = read_loan(&mut &[b'{', 0xff, b'}'][..]).unwrap_err();
+        assert_eq!(error_status(&error), 400);
+        let error = read_loan(&mut &b"{\"loan_amount\":"[..]).unwrap_err();
+        assert_eq!(error_status(&error), 400);
+    }
+}
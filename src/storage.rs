@@ -0,0 +1,72 @@
+use anyhow::{Context, Error};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::history::LoanVersion;
+use crate::Loan;
+
+/// Environment variable used to choose where the loan book is stored.
+pub const BOOK_PATH_VARIABLE: &str = "INTEREST_APP_BOOK";
+pub const DEFAULT_BOOK_PATH: &str = "loan_book.json";
+
+/// Everything needed to restore a LoanCalculator's loans between sessions.
+#[derive(Debug, Deserialize)]
+pub struct LoanBook {
+    pub next_loan_id: u32,
+    pub loans: BTreeMap<u32, Loan>,
+    // books saved before archiving was added have no archived loans
+    #[serde(default)]
+    pub archived_loans: BTreeMap<u32, Loan>,
+    #[serde(default)]
+    pub history: BTreeMap<u32, Vec<LoanVersion>>,
+}
+
+/// Stores the loan book as a JSON file.
+#[derive(Debug)]
+pub struct JsonStorage {
+    path: PathBuf,
+}
+
+impl JsonStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        JsonStorage { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Loads the book, returning None when the file does not exist yet.
+    pub fn load(&self) -> Result<Option<LoanBook>, Error> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&self.path).with_context(|| format!("Could not read {}", self.path.display()))?;
+        let book = serde_json::from_str(&contents).with_context(|| format!("Could not parse {}", self.path.display()))?;
+        Ok(Some(book))
+    }
+
+    pub fn save(
+        &self,
+        next_loan_id: u32,
+        loans: &BTreeMap<u32, Loan>,
+        archived_loans: &BTreeMap<u32, Loan>,
+        history: &BTreeMap<u32, Vec<LoanVersion>>,
+    ) -> Result<(), Error> {
+        #[derive(Serialize)]
+        struct LoanBookRef<'a> {
+            next_loan_id: u32,
+            loans: &'a BTreeMap<u32, Loan>,
+            archived_loans: &'a BTreeMap<u32, Loan>,
+            history: &'a BTreeMap<u32, Vec<LoanVersion>>,
+        }
+        let contents = serde_json::to_string_pretty(&LoanBookRef { next_loan_id, loans, archived_loans, history })?;
+        // write to a temporary file first so an interrupted save never leaves a half written book
+        let temporary_path = self.path.with_extension("json.tmp");
+        fs::write(&temporary_path, contents).with_context(|| format!("Could not write {}", temporary_path.display()))?;
+        fs::rename(&temporary_path, &self.path).with_context(|| format!("Could not write {}", self.path.display()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+//! Dates and loans shared by the unit tests.
+
+use chrono::NaiveDate;
+use rust_decimal_macros::dec;
+
+use crate::{Loan, MarketData};
+
+pub fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+/// A 1,000,000 USD bullet loan from `start` to `end` at 5% plus a 1% margin, not yet calculated.
+pub fn loan(start: NaiveDate, end: NaiveDate) -> Loan {
+    let mut loan = Loan::new();
+    loan.start_date = start;
+    loan.end_date = end;
+    loan.loan_amount = dec!(1000000);
+    loan
+}
+
+/// The loan with its interest calculated without any fixings or holiday calendars.
+pub fn calculated(mut loan: Loan) -> Loan {
+    loan.calculate_interest(&MarketData::default()).unwrap();
+    loan
+}
+
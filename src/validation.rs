@@ -0,0 +1,288 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use std::fmt;
+
+use crate::fixings::FixingFallback;
+use crate::repayment::RepaymentType;
+use crate::Loan;
+
+/// Rates above this are taken to be a typing mistake, e.g. 5 entered as 500%.
+const MAX_RATE: Decimal = dec!(1);
+/// Lowest rate accepted when negative rates are allowed.
+const MIN_NEGATIVE_RATE: Decimal = dec!(-0.1);
+
+/// Limits applied to loan parameters before a loan is calculated or saved.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ValidationRules {
+    /// Accept base rates, margins and fixed fallback rates below zero, down to -10%.
+    pub allow_negative_rates: bool,
+}
+
+/// A problem with one loan parameter, named as in the loan book and CSV imports.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Every problem found with a loan, returned together so they can all be fixed at once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationErrors(pub Vec<FieldError>);
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors: Vec<String> = self.0.iter().map(|error| format!("{}: {}", error.field, error.message)).collect();
+        write!(f, "{}", errors.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+impl ValidationRules {
+    pub fn validate(&self, loan: &Loan) -> Result<(), ValidationErrors> {
+        let mut errors = Vec::new();
+        let mut error = |field: &str, message: String| {
+            errors.push(FieldError {
+                field: field.to_string(),
+                message,
+            })
+        };
+
+        if loan.end_date <= loan.start_date {
+            error("end_date", format!("must be after the start date {}", loan.start_date));
+        }
+        // a facility can start undrawn and be drawn through its ledger
+        if loan.facility.is_some() && loan.loan_amount < Decimal::ZERO {
+            error("loan_amount", "must not be negative".to_string());
+        } else if loan.facility.is_none() && loan.loan_amount <= Decimal::ZERO {
+            error("loan_amount", "must be positive".to_string());
+        }
+
+        if let Some(message) = self.check_rate(loan.base_interest_rate) {
+            error("base_interest_rate", message);
+        }
+        for (date, rate) in loan.base_rate_schedule.iter() {
+            if let Some(message) = self.check_rate(*rate) {
+                error(&format!("base_rate_schedule.{}", date), message);
+            }
+        }
+        if let FixingFallback::FixedRate(rate) = loan.fixing_fallback {
+            if let Some(message) = self.check_rate(rate) {
+                error("fixing_fallback", message);
+            }
+        }
+        if let Some(message) = self.check_rate(loan.margin) {
+            error("margin", message);
+        }
+        if loan.default_margin < Decimal::ZERO || loan.default_margin > MAX_RATE {
+            error("default_margin", "must be between 0% and 100%".to_string());
+        }
+
+        let schedule = &loan.repayment_schedule;
+        for (date, amount) in schedule.custom_repayments.iter() {
+            if *amount <= Decimal::ZERO {
+                error(&format!("repayment_schedule.custom_repayments.{}", date), "must be positive".to_string());
+            }
+        }
+        if schedule.repayment_type == RepaymentType::Balloon
+            && (schedule.balloon_amount < Decimal::ZERO || schedule.balloon_amount > loan.loan_amount)
+        {
+            error("repayment_schedule.balloon_amount", "must be between zero and the loan amount".to_string());
+        }
+
+        if let Some(facility) = loan.facility {
+            if facility.limit < loan.loan_amount {
+                error("facility.limit", "must be at least the initially drawn loan amount".to_string());
+            }
+            if facility.commitment_fee_rate < Decimal::ZERO || facility.commitment_fee_rate > MAX_RATE {
+                error("facility.commitment_fee_rate", "must be between 0% and 100%".to_string());
+            }
+        }
+        for event in loan.ledger.iter() {
+            if event.date < loan.start_date || event.date > loan.end_date {
+                error(&format!("ledger.{}", event.date), "must be within the loan term".to_string());
+            }
+        }
+        for event in loan.overdue_events.iter() {
+            let field = format!("overdue_events.{}", event.due_date);
+            if event.due_date < loan.start_date || event.due_date > loan.end_date {
+                error(&field, "must be due within the loan term".to_string());
+            }
+            if event.amount <= Decimal::ZERO {
+                error(&field, "must be positive".to_string());
+            }
+            if event.paid_date.is_some_and(|paid_date| paid_date <= event.due_date) {
+                error(&field, "must be paid after the due date".to_string());
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationErrors(errors))
+        }
+    }
+
+    fn check_rate(&self, rate: Decimal) -> Option<String> {
+        if rate > MAX_RATE {
+            Some(format!("{}% is above the maximum of 100%", (rate * dec!(100)).normalize()))
+        } else if rate < Decimal::ZERO && !self.allow_negative_rates {
+            Some(format!(
+                "{}% is negative, negative rates are not allowed by the validation rules",
+                (rate * dec!(100)).normalize()
+            ))
+        } else if rate < MIN_NEGATIVE_RATE {
+            Some(format!("{}% is below the minimum of -10%", (rate * dec!(100)).normalize()))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::facility::{Facility, LedgerEvent, LedgerEventType};
+    use crate::overdue::OverdueEvent;
+    use crate::test_support::{date, loan};
+
+    fn valid_loan() -> Loan {
+        loan(date(2024, 1, 1), date(2024, 12, 31))
+    }
+
+    /// Fields rejected by `rules`, in the order they were checked.
+    fn rejected(rules: ValidationRules, loan: &Loan) -> Vec<String> {
+        match rules.validate(loan) {
+            Ok(()) => Vec::new(),
+            Err(ValidationErrors(errors)) => errors.into_iter().map(|error| error.field).collect(),
+        }
+    }
+
+    fn rejected_by_default(loan: &Loan) -> Vec<String> {
+        rejected(ValidationRules::default(), loan)
+    }
+
+    fn facility(limit: Decimal) -> Option<Facility> {
+        Some(Facility {
+            limit,
+            commitment_fee_rate: dec!(0.005),
+        })
+    }
+
+    #[test]
+    fn valid_loans_pass() {
+        assert!(rejected_by_default(&valid_loan()).is_empty());
+    }
+
+    #[test]
+    fn end_date_must_be_after_the_start_date() {
+        let mut loan = valid_loan();
+        loan.end_date = loan.start_date;
+        assert_eq!(rejected_by_default(&loan), ["end_date"]);
+    }
+
+    #[test]
+    fn loan_amount_must_be_positive_unless_it_is_a_facility() {
+        let mut loan = valid_loan();
+        loan.loan_amount = Decimal::ZERO;
+        assert_eq!(rejected_by_default(&loan), ["loan_amount"]);
+
+        loan.facility = facility(dec!(1000));
+        assert!(rejected_by_default(&loan).is_empty());
+        loan.loan_amount = dec!(-1);
+        assert_eq!(rejected_by_default(&loan), ["loan_amount"]);
+    }
+
+    #[test]
+    fn rates_above_100_percent_are_rejected() {
+        let mut loan = valid_loan();
+        loan.base_interest_rate = dec!(1.5);
+        loan.margin = dec!(1.01);
+        loan.base_rate_schedule.insert(date(2024, 6, 1), dec!(2));
+        loan.fixing_fallback = FixingFallback::FixedRate(dec!(1.1));
+        assert_eq!(
+            rejected_by_default(&loan),
+            ["base_interest_rate", "base_rate_schedule.2024-06-01", "fixing_fallback", "margin"]
+        );
+    }
+
+    #[test]
+    fn negative_rates_need_to_be_allowed() {
+        let mut loan = valid_loan();
+        loan.base_interest_rate = dec!(-0.005);
+        assert_eq!(rejected_by_default(&loan), ["base_interest_rate"]);
+
+        let allow_negative_rates = ValidationRules {
+            allow_negative_rates: true,
+        };
+        assert!(rejected(allow_negative_rates, &loan).is_empty());
+        loan.base_interest_rate = dec!(-0.11);
+        assert_eq!(rejected(allow_negative_rates, &loan), ["base_interest_rate"]);
+    }
+
+    #[test]
+    fn default_margin_must_be_between_0_and_100_percent() {
+        let mut loan = valid_loan();
+        loan.default_margin = dec!(-0.01);
+        assert_eq!(rejected_by_default(&loan), ["default_margin"]);
+        loan.default_margin = dec!(1.01);
+        assert_eq!(rejected_by_default(&loan), ["default_margin"]);
+    }
+
+    #[test]
+    fn repayments_must_be_positive_and_balloons_within_the_loan_amount() {
+        let mut loan = valid_loan();
+        loan.repayment_schedule.repayment_type = RepaymentType::Custom;
+        loan.repayment_schedule.custom_repayments.insert(date(2024, 6, 1), Decimal::ZERO);
+        assert_eq!(rejected_by_default(&loan), ["repayment_schedule.custom_repayments.2024-06-01"]);
+
+        let mut loan = valid_loan();
+        loan.repayment_schedule.repayment_type = RepaymentType::Balloon;
+        loan.repayment_schedule.balloon_amount = loan.loan_amount + Decimal::ONE;
+        assert_eq!(rejected_by_default(&loan), ["repayment_schedule.balloon_amount"]);
+    }
+
+    #[test]
+    fn facility_limit_and_fee_are_checked() {
+        let mut loan = valid_loan();
+        loan.facility = Some(Facility {
+            limit: loan.loan_amount - Decimal::ONE,
+            commitment_fee_rate: dec!(1.5),
+        });
+        assert_eq!(rejected_by_default(&loan), ["facility.limit", "facility.commitment_fee_rate"]);
+    }
+
+    #[test]
+    fn ledger_events_must_be_within_the_term() {
+        let mut loan = valid_loan();
+        loan.facility = facility(dec!(2000000));
+        loan.ledger.push(LedgerEvent {
+            date: date(2025, 1, 1),
+            event_type: LedgerEventType::Drawdown,
+            amount: dec!(1000),
+        });
+        assert_eq!(rejected_by_default(&loan), ["ledger.2025-01-01"]);
+    }
+
+    #[test]
+    fn overdue_payments_are_checked() {
+        let mut loan = valid_loan();
+        loan.overdue_events = vec![
+            OverdueEvent {
+                due_date: date(2025, 2, 1),
+                amount: dec!(1000),
+                paid_date: None,
+            },
+            OverdueEvent {
+                due_date: date(2024, 3, 1),
+                amount: Decimal::ZERO,
+                paid_date: Some(date(2024, 3, 1)),
+            },
+        ];
+        assert_eq!(
+            rejected_by_default(&loan),
+            ["overdue_events.2025-02-01", "overdue_events.2024-03-01", "overdue_events.2024-03-01"]
+        );
+    }
+}